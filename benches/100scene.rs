@@ -1,9 +1,10 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
+use rusttracer::structs::film::Film;
 use rusttracer::structs::vec3::Vec3;
 use rusttracer::structs::viewport::Viewport;
 
-fn setup_and_run(samples: u32) -> Vec<u8> {
+fn setup_and_run(samples: u32) -> Film {
     let aspect_ratio = 16. / 9.;
     let img_width = 1200u32;
     let img_height = (img_width as f64 / aspect_ratio) as u32;
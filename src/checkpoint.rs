@@ -0,0 +1,216 @@
+//! Saving the state of an unfinished render to disk and picking it up again.
+//!
+//! A checkpoint holds everything accumulated on the film: the weighted
+//! radiance sums, sample counts and noise statistics of every pixel, the
+//! light traced onto it and the AOVs when recorded. Samplers have no
+//! state of their own besides their name and seed, the next sample of a
+//! pixel follows from how many it already has, so resuming continues
+//! exactly where the render stopped.
+//! A hash of the settings the image depends on guards against resuming
+//! with different ones.
+
+use crate::structs::film::{AovPixel, Film, FilmPixel};
+use crate::structs::vec3::Vec3;
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"RTCK";
+const VERSION: u32 = 3;
+// Longer sampler names only come from damaged files.
+const MAX_SAMPLER_NAME: usize = 256;
+
+/// What a checkpoint was rendered with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckpointInfo {
+    pub settings_hash: u64,
+    pub sampler: String,
+    pub seed: u64,
+}
+
+/// Writes the film to `path`. The file is written next to it first and then
+/// moved over it, so an interrupted write keeps the previous checkpoint.
+pub fn save(film: &Film, info: &CheckpointInfo, path: &Path) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".partial");
+
+    {
+        let mut out = BufWriter::new(File::create(&temporary)?);
+        write(&mut out, film, info)?;
+        out.flush()?;
+    }
+    fs::rename(&temporary, path)
+}
+
+/// Reads a checkpoint into `film`, which has to be empty and of the same size
+/// and AOV layout the checkpoint was saved with.
+pub fn load(film: &mut Film, path: &Path) -> io::Result<CheckpointInfo> {
+    let mut input = BufReader::new(File::open(path)?);
+    read(&mut input, film)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_vec3<W: Write>(out: &mut W, v: &Vec3) -> io::Result<()> {
+    for c in [v.x_, v.y_, v.z_].iter() {
+        out.write_all(&c.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn write<W: Write>(out: &mut W, film: &Film, info: &CheckpointInfo) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&info.settings_hash.to_le_bytes())?;
+    out.write_all(&(info.sampler.len() as u32).to_le_bytes())?;
+    out.write_all(info.sampler.as_bytes())?;
+    out.write_all(&info.seed.to_le_bytes())?;
+    out.write_all(&film.width().to_le_bytes())?;
+    out.write_all(&film.height().to_le_bytes())?;
+    out.write_all(&[film.aovs().is_some() as u8])?;
+
+    for pixel in film.pixels() {
+        write_vec3(out, &pixel.radiance_sum)?;
+        out.write_all(&pixel.weight_sum.to_le_bytes())?;
+        out.write_all(&pixel.samples.to_le_bytes())?;
+        out.write_all(&pixel.luminance_sum.to_le_bytes())?;
+        out.write_all(&pixel.luminance_sq_sum.to_le_bytes())?;
+    }
+    for light in film.light() {
+        write_vec3(out, light)?;
+    }
+
+    if let Some(aovs) = film.aovs() {
+        for aov in aovs {
+            write_vec3(out, &aov.albedo)?;
+            write_vec3(out, &aov.normal)?;
+            write_vec3(out, &aov.position)?;
+            out.write_all(&aov.depth.to_le_bytes())?;
+            out.write_all(&aov.motion.0.to_le_bytes())?;
+            out.write_all(&aov.motion.1.to_le_bytes())?;
+            out.write_all(&aov.hits.to_le_bytes())?;
+            out.write_all(&aov.material_id.to_le_bytes())?;
+            out.write_all(&aov.object_id.to_le_bytes())?;
+            for (direct, indirect) in aov.lobes.iter().chain(aov.light_lobes.iter()) {
+                write_vec3(out, direct)?;
+                write_vec3(out, indirect)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_u8<R: Read>(input: &mut R) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32<R: Read>(input: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f64<R: Read>(input: &mut R) -> io::Result<f64> {
+    Ok(f64::from_bits(read_u64(input)?))
+}
+
+fn read_vec3<R: Read>(input: &mut R) -> io::Result<Vec3> {
+    Ok(Vec3::new(
+        read_f64(input)?,
+        read_f64(input)?,
+        read_f64(input)?,
+    ))
+}
+
+fn read<R: Read>(input: &mut R, film: &mut Film) -> io::Result<CheckpointInfo> {
+    let mut magic = [0; 4];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid(String::from("not a checkpoint")));
+    }
+    let version = read_u32(input)?;
+    if version != VERSION {
+        return Err(invalid(format!(
+            "unsupported checkpoint version {}",
+            version
+        )));
+    }
+
+    let settings_hash = read_u64(input)?;
+    let length = read_u32(input)? as usize;
+    if length > MAX_SAMPLER_NAME {
+        return Err(invalid(format!("sampler name of {} bytes", length)));
+    }
+    let mut sampler = vec![0; length];
+    input.read_exact(&mut sampler)?;
+    let sampler =
+        String::from_utf8(sampler).map_err(|_| invalid(String::from("bad sampler name")))?;
+    let seed = read_u64(input)?;
+
+    let (width, height) = (read_u32(input)?, read_u32(input)?);
+    let has_aovs = read_u8(input)? != 0;
+    if (width, height) != (film.width(), film.height()) {
+        return Err(invalid(format!(
+            "checkpoint is {}x{}, the image {}x{}",
+            width,
+            height,
+            film.width(),
+            film.height()
+        )));
+    }
+    if has_aovs != film.aovs().is_some() {
+        return Err(invalid(String::from(
+            "checkpoint and image disagree on recording AOVs",
+        )));
+    }
+
+    for pixel in film.pixels_mut() {
+        *pixel = FilmPixel {
+            radiance_sum: read_vec3(input)?,
+            weight_sum: read_f64(input)?,
+            samples: read_u32(input)?,
+            luminance_sum: read_f64(input)?,
+            luminance_sq_sum: read_f64(input)?,
+        };
+    }
+    for light in film.light_mut() {
+        *light = read_vec3(input)?;
+    }
+
+    if let Some(aovs) = film.aovs_mut() {
+        for aov in aovs {
+            let mut pixel = AovPixel {
+                albedo: read_vec3(input)?,
+                normal: read_vec3(input)?,
+                position: read_vec3(input)?,
+                depth: read_f64(input)?,
+                motion: (read_f64(input)?, read_f64(input)?),
+                hits: read_u32(input)?,
+                material_id: read_u32(input)?,
+                object_id: read_u32(input)?,
+                ..AovPixel::new()
+            };
+            for lobe in pixel.lobes.iter_mut().chain(pixel.light_lobes.iter_mut()) {
+                *lobe = (read_vec3(input)?, read_vec3(input)?);
+            }
+            *aov = pixel;
+        }
+    }
+
+    Ok(CheckpointInfo {
+        settings_hash,
+        sampler,
+        seed,
+    })
+}
@@ -0,0 +1,242 @@
+use crate::output::aov::{self, Pass};
+use crate::structs::film::Film;
+use crate::structs::vec3::Vec3;
+
+use rayon::prelude::*;
+
+/// Post-process removing sampling noise from a rendered film.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Denoiser {
+    None,
+    /// Edge avoiding à-trous wavelet filter, after Dammertz et al. 2010.
+    ATrous(ATrousSettings),
+}
+
+impl Denoiser {
+    pub fn from_name(name: &str) -> Option<Denoiser> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Some(Denoiser::None),
+            "atrous" | "a-trous" | "wavelet" => Some(Denoiser::ATrous(ATrousSettings::default())),
+            _ => None,
+        }
+    }
+
+    /// Replaces the radiance of the film with the denoised one.
+    /// Works best on films recorded with AOVs, without them only colors guide the filter.
+    pub fn apply(&self, film: &mut Film) {
+        if let Denoiser::ATrous(settings) = self {
+            let denoised = atrous(film, settings);
+            film.set_colors(&denoised);
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ATrousSettings {
+    /// Number of filter passes, the footprint doubles with every pass.
+    pub iterations: u32,
+    /// Edge stopping sensitivity to the noisy illumination, it halves every pass.
+    pub sigma_color: f64,
+    pub sigma_normal: f64,
+    pub sigma_albedo: f64,
+    /// Relative to the pixel's distance from the camera.
+    pub sigma_depth: f64,
+}
+
+impl Default for ATrousSettings {
+    fn default() -> ATrousSettings {
+        ATrousSettings {
+            iterations: 5,
+            sigma_color: 0.6,
+            sigma_normal: 0.3,
+            sigma_albedo: 0.1,
+            sigma_depth: 0.05,
+        }
+    }
+}
+
+// Feature buffers guiding the filter.
+struct Guides {
+    albedo: Vec<Vec3>,
+    normal: Vec<Vec3>,
+    depth: Vec<f64>,
+}
+
+fn guides(film: &Film) -> Option<Guides> {
+    let passes = aov::passes(film);
+    let find = |name: &str| -> Option<&Pass> { passes.iter().find(|p| p.name == name) };
+
+    Some(Guides {
+        albedo: find("albedo")?.values.clone(),
+        normal: find("normal")?.values.clone(),
+        depth: find("depth")?.values.iter().map(|d| d.x_).collect(),
+    })
+}
+
+// Albedo is divided out before filtering and multiplied back after,
+// so texture and color detail does not get blurred.
+fn demodulation(albedo: &Vec3) -> Vec3 {
+    let f = |c: f64| if c > 1e-3 { c } else { 1. };
+    Vec3::new(f(albedo.x_), f(albedo.y_), f(albedo.z_))
+}
+
+fn distance_squared(a: &Vec3, b: &Vec3) -> f64 {
+    let d = a - b;
+    Vec3::dot(&d, &d)
+}
+
+fn atrous(film: &Film, settings: &ATrousSettings) -> Vec<Vec3> {
+    const KERNEL: [f64; 5] = [1. / 16., 1. / 4., 3. / 8., 1. / 4., 1. / 16.];
+
+    let (width, height) = (film.width() as i64, film.height() as i64);
+    let guides = guides(film);
+
+    let mut illumination: Vec<Vec3> = match &guides {
+        Some(guides) => film
+            .colors()
+            .iter()
+            .zip(guides.albedo.iter())
+            .map(|(col, albedo)| {
+                let d = demodulation(albedo);
+                Vec3::new(col.x_ / d.x_, col.y_ / d.y_, col.z_ / d.z_)
+            })
+            .collect(),
+        None => film.colors(),
+    };
+
+    for iteration in 0..settings.iterations {
+        let step = 1i64 << iteration;
+        let sigma_color = settings.sigma_color / 2f64.powi(iteration as i32);
+        let input = &illumination;
+
+        illumination = (0..width * height)
+            .into_par_iter()
+            .map(|index| {
+                let (x, y) = (index % width, index / width);
+                let center = input[index as usize];
+
+                let mut sum = Vec3::zero();
+                let mut weight_sum = 0.;
+                for (ky, ry) in KERNEL.iter().enumerate() {
+                    for (kx, rx) in KERNEL.iter().enumerate() {
+                        let qx = x + (kx as i64 - 2) * step;
+                        let qy = y + (ky as i64 - 2) * step;
+                        if qx < 0 || qy < 0 || qx >= width || qy >= height {
+                            continue;
+                        }
+                        let q = (qy * width + qx) as usize;
+                        let p = index as usize;
+
+                        let mut exponent =
+                            distance_squared(&center, &input[q]) / (sigma_color * sigma_color);
+                        if let Some(guides) = &guides {
+                            exponent += distance_squared(&guides.normal[p], &guides.normal[q])
+                                / (settings.sigma_normal * settings.sigma_normal);
+                            exponent += distance_squared(&guides.albedo[p], &guides.albedo[q])
+                                / (settings.sigma_albedo * settings.sigma_albedo);
+
+                            let (dp, dq) = (guides.depth[p], guides.depth[q]);
+                            if dp.is_finite() != dq.is_finite() {
+                                continue;
+                            }
+                            if dp.is_finite() {
+                                let relative = (dp - dq) / (settings.sigma_depth * dp.max(1e-6));
+                                exponent += relative * relative;
+                            }
+                        }
+
+                        let weight = rx * ry * (-exponent).exp();
+                        sum = sum + weight * input[q];
+                        weight_sum += weight;
+                    }
+                }
+
+                if weight_sum > 0. {
+                    sum / weight_sum
+                } else {
+                    center
+                }
+            })
+            .collect();
+    }
+
+    match &guides {
+        Some(guides) => illumination
+            .iter()
+            .zip(guides.albedo.iter())
+            .map(|(col, albedo)| demodulation(albedo) * col)
+            .collect(),
+        None => illumination,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::film::AovSample;
+
+    // Film with a sample per pixel of cheap hashed noise and AOVs made of
+    // two planes at different depths, so every guide takes part.
+    fn noisy_film(color: impl Fn(u32, u32) -> Vec3) -> Film {
+        let (width, height) = (12, 9);
+        let mut film = Film::new(width, height).with_aovs();
+        for y in 0..height {
+            for x in 0..width {
+                film.pixel_mut(x, y).add_sample(color(x, y));
+            }
+        }
+        for (index, aov) in film.aovs_mut().unwrap().iter_mut().enumerate() {
+            let near = index as u32 % width < width / 2;
+            aov.add_sample(&AovSample {
+                albedo: Vec3::new(0.8, 0.6, 0.4),
+                normal: if near {
+                    Vec3::new(0., 0., 1.)
+                } else {
+                    Vec3::new(0., 1., 0.)
+                },
+                position: Vec3::zero(),
+                depth: if near { 2. } else { 7. },
+                motion: (0., 0.),
+                material_id: 1,
+                object_id: 1,
+            });
+        }
+        film
+    }
+
+    fn noise(x: u32, y: u32) -> Vec3 {
+        let hash = |n: u32| (n.wrapping_mul(2_654_435_761) >> 16) as f64 / 65536.;
+        Vec3::new(hash(x * 31 + y), hash(x + y * 17), hash(x * y + 5))
+    }
+
+    fn atrous_denoiser() -> Denoiser {
+        Denoiser::ATrous(ATrousSettings::default())
+    }
+
+    #[test]
+    fn atrous_is_deterministic() {
+        let mut first = noisy_film(noise);
+        let mut second = noisy_film(noise);
+        atrous_denoiser().apply(&mut first);
+        atrous_denoiser().apply(&mut second);
+
+        for (a, b) in first.colors().iter().zip(second.colors().iter()) {
+            assert_eq!(a.x_.to_bits(), b.x_.to_bits());
+            assert_eq!(a.y_.to_bits(), b.y_.to_bits());
+            assert_eq!(a.z_.to_bits(), b.z_.to_bits());
+        }
+    }
+
+    #[test]
+    fn atrous_keeps_constant_films() {
+        let constant = Vec3::new(0.25, 0.5, 0.75);
+        let mut film = noisy_film(|_, _| constant);
+        atrous_denoiser().apply(&mut film);
+
+        for col in film.colors() {
+            assert!((col.x_ - constant.x_).abs() < 1e-12);
+            assert!((col.y_ - constant.y_).abs() < 1e-12);
+            assert!((col.z_ - constant.z_).abs() < 1e-12);
+        }
+    }
+}
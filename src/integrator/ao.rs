@@ -0,0 +1,51 @@
+use crate::integrator::{trace, unoccluded, Integrator};
+use crate::sampler::{warp, Sampler};
+use crate::structs::film::Radiance;
+use crate::structs::hitable::HitList;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::Vec3;
+
+/// Ambient occlusion: how much of the hemisphere above the first hit is
+/// open within `distance`, white where nothing is in the way. Fast to
+/// converge and handy to judge geometry without any lights.
+#[derive(Debug, Clone)]
+pub struct AmbientOcclusionIntegrator {
+    distance: f64,
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn new(distance: f64) -> AmbientOcclusionIntegrator {
+        AmbientOcclusionIntegrator { distance }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn name(&self) -> &'static str {
+        "ao"
+    }
+
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        if depth == 0 {
+            return (Radiance::zero(), 0);
+        }
+        let hit_rec = match trace(scene, r) {
+            Some(hit_rec) => hit_rec,
+            None => return (Radiance::direct(Vec3::new(1., 1., 1.), None), 0),
+        };
+
+        // Cosine weighted directions make the visible fraction the estimate.
+        let direction = warp::cosine_hemisphere(&hit_rec.out_normal, sampler.get_2d());
+        let open = if unoccluded(scene, &hit_rec.hit_point, &direction, self.distance) {
+            Vec3::new(1., 1., 1.)
+        } else {
+            Vec3::zero()
+        };
+        (Radiance::direct(open, Some(hit_rec.material.lobe())), 1)
+    }
+}
@@ -0,0 +1,667 @@
+use crate::integrator::{
+    is_black, light_count, russian_roulette, sample_indexed_light, trace, unoccluded, Integrator,
+};
+use crate::sampler::{warp, Sampler};
+use crate::structs::camera::Camera;
+use crate::structs::film::{Lobe, Radiance};
+use crate::structs::hitable::{HitList, HitRecord};
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+use std::f64::consts::PI;
+
+/// Bidirectional path tracer after Veach: a path is started from the
+/// camera and another one from a light, and every vertex of the one is
+/// connected to every vertex of the other. All the ways of building a
+/// path that way are weighted against each other with multiple importance
+/// sampling. Connecting light paths straight to the lens lands light on
+/// arbitrary pixels, which finds caustics and lights hidden behind glass
+/// that path tracing hardly ever hits.
+///
+/// Light paths start from the sphere lights only, the environment is
+/// reached from the camera side. Lights are picked like for shadow rays,
+/// the environment included, and a sample that picks the environment goes
+/// without a light path.
+#[derive(Debug, Clone)]
+pub struct BdptIntegrator {
+    roulette_bounces: Option<u32>,
+}
+
+impl BdptIntegrator {
+    pub fn new() -> BdptIntegrator {
+        BdptIntegrator {
+            roulette_bounces: Some(3),
+        }
+    }
+
+    /// Bounces after which camera and light paths are ended by Russian
+    /// roulette, `None` follows them to the ray depth. Every camera vertex
+    /// is joined to every light vertex, so long paths get expensive.
+    pub fn with_russian_roulette(mut self, min_bounces: Option<u32>) -> BdptIntegrator {
+        self.roulette_bounces = min_bounces;
+        self
+    }
+}
+
+impl Default for BdptIntegrator {
+    fn default() -> BdptIntegrator {
+        BdptIntegrator::new()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Kind {
+    Camera,
+    /// Point on the light of this index in the scene's lights.
+    Light(usize),
+    /// The environment, `point` holds the direction it is seen in.
+    Environment,
+    Surface,
+}
+
+// Vertex of a camera or light path. Densities are per area of the vertex,
+// per solid angle for the environment.
+struct Vertex {
+    kind: Kind,
+    point: Point3,
+    // Facing the side the path arrived from, zero for the camera.
+    normal: Vec3,
+    hit: Option<HitRecord>,
+    // Throughput of the path up to the vertex.
+    beta: Vec3,
+    // Density of the vertex being found from the one before it along the
+    // path, and from the one after it when the path is traced backwards.
+    pdf_fwd: f64,
+    pdf_rev: f64,
+    delta: bool,
+}
+
+impl Vertex {
+    fn surface(hit: HitRecord, beta: Vec3) -> Vertex {
+        Vertex {
+            kind: Kind::Surface,
+            point: hit.hit_point,
+            normal: hit.out_normal,
+            delta: hit.material.is_specular(),
+            hit: Some(hit),
+            beta,
+            pdf_fwd: 0.,
+            pdf_rev: 0.,
+        }
+    }
+
+    fn camera(point: Point3, delta: bool) -> Vertex {
+        Vertex {
+            kind: Kind::Camera,
+            point,
+            normal: Vec3::zero(),
+            hit: None,
+            beta: Vec3::new(1., 1., 1.),
+            pdf_fwd: 1.,
+            pdf_rev: 0.,
+            delta,
+        }
+    }
+
+    // Whether another path can be joined to this vertex.
+    fn is_connectible(&self) -> bool {
+        match self.kind {
+            Kind::Camera | Kind::Light(_) => true,
+            Kind::Environment => false,
+            Kind::Surface => !self.delta,
+        }
+    }
+
+    // Index of the light this vertex lies on, also for surfaces of lights
+    // that camera paths hit.
+    fn light(&self, scene: &HitList) -> Option<usize> {
+        match (self.kind, &self.hit) {
+            (Kind::Light(index), _) => Some(index),
+            (Kind::Surface, Some(hit)) => scene
+                .lights
+                .iter()
+                .position(|light| light.object_id == hit.object_id),
+            _ => None,
+        }
+    }
+
+    fn direction_to(&self, next: &Vertex) -> Vec3 {
+        match next.kind {
+            Kind::Environment => next.point,
+            _ => next.point - self.point,
+        }
+    }
+
+    // What the vertex lets through towards `direction`, with the cosine.
+    fn f(&self, direction: &Vec3) -> Vec3 {
+        match (self.kind, &self.hit) {
+            (Kind::Surface, Some(hit)) => hit.material.eval(hit, direction),
+            (Kind::Light(_), _) => {
+                let cos = Vec3::dot(&self.normal, &Vec3::unit_vector(*direction)).max(0.);
+                Vec3::new(cos, cos, cos)
+            }
+            _ => Vec3::zero(),
+        }
+    }
+
+    // Light the vertex gives off towards the previous vertex of a camera path.
+    fn emitted(&self, scene: &HitList) -> Vec3 {
+        match (self.kind, &self.hit) {
+            (Kind::Surface, Some(hit)) => hit.material.emitted(hit),
+            (Kind::Environment, _) => scene.environment.radiance(&self.point),
+            _ => Vec3::zero(),
+        }
+    }
+
+    // Turns a density per solid angle at this vertex into one per area at
+    // `next`.
+    fn convert_density(&self, pdf: f64, next: &Vertex) -> f64 {
+        if next.kind == Kind::Environment {
+            return pdf;
+        }
+        let w = next.point - self.point;
+        let distance_sq = Vec3::dot(&w, &w);
+        if distance_sq == 0. {
+            return 0.;
+        }
+        // The lens is not a surface light arrives at under an angle.
+        let cos = match next.kind {
+            Kind::Camera => 1.,
+            _ => Vec3::dot(&next.normal, &w).abs() / distance_sq.sqrt(),
+        };
+        pdf * cos / distance_sq
+    }
+
+    // Density of `next` being found by extending a path from this vertex.
+    fn pdf(&self, scene: &HitList, camera: &dyn Camera, next: &Vertex) -> f64 {
+        let direction = self.direction_to(next);
+        let pdf = match (self.kind, &self.hit) {
+            (Kind::Camera, _) => camera
+                .importance_pdf(&Ray::new(self.point, direction))
+                .map_or(0., |(_, pdf)| pdf),
+            (Kind::Surface, Some(hit)) if self.light(scene).is_none() => {
+                hit.material.pdf(hit, &direction)
+            }
+            (Kind::Environment, _) => 0.,
+            _ => return self.pdf_light(scene, next),
+        };
+        self.convert_density(pdf, next)
+    }
+
+    // Density of a light path leaving this vertex on a light hitting `next`.
+    fn pdf_light(&self, scene: &HitList, next: &Vertex) -> f64 {
+        if self.light(scene).is_none() || next.kind == Kind::Environment {
+            return 0.;
+        }
+        let direction = Vec3::unit_vector(next.point - self.point);
+        let cos = Vec3::dot(&self.normal, &direction).max(0.);
+        self.convert_density(cos / PI, next)
+    }
+
+    // Density of a light path starting at this vertex, with the choice of
+    // the light.
+    fn pdf_light_origin(&self, scene: &HitList) -> f64 {
+        let count = light_count(scene) as f64;
+        if self.kind == Kind::Environment {
+            return 1. / (count * 4. * PI);
+        }
+        match self.light(scene) {
+            Some(index) => {
+                let radius = scene.lights[index].radius;
+                1. / (count * 4. * PI * radius * radius)
+            }
+            None => 0.,
+        }
+    }
+}
+
+// Follows `ray` through the scene until the path holds `max_vertices`,
+// adding a vertex per hit. Camera paths also end on the environment.
+#[allow(clippy::too_many_arguments)]
+fn random_walk(
+    scene: &HitList,
+    sampler: &mut dyn Sampler,
+    mut ray: Ray,
+    mut beta: Vec3,
+    mut pdf_fwd: f64,
+    max_vertices: usize,
+    roulette_bounces: Option<u32>,
+    path: &mut Vec<Vertex>,
+) {
+    let from_camera = path[0].kind == Kind::Camera;
+    while path.len() < max_vertices {
+        let previous = path.len() - 1;
+        let hit_rec = match trace(scene, &ray) {
+            Some(hit_rec) => hit_rec,
+            None => {
+                if from_camera && !scene.environment.is_black() {
+                    path.push(Vertex {
+                        kind: Kind::Environment,
+                        point: Vec3::unit_vector(ray.direction()),
+                        normal: Vec3::zero(),
+                        hit: None,
+                        beta,
+                        pdf_fwd,
+                        pdf_rev: 0.,
+                        delta: false,
+                    });
+                }
+                return;
+            }
+        };
+
+        let material = hit_rec.material;
+        let mut vertex = Vertex::surface(hit_rec, beta);
+        vertex.pdf_fwd = path[previous].convert_density(pdf_fwd, &vertex);
+        path.push(vertex);
+        if path.len() == max_vertices {
+            return;
+        }
+
+        let current = &path[previous + 1];
+        let hit_rec = current.hit.as_ref().unwrap();
+        let scattered = match material.scatter(&ray, hit_rec, sampler) {
+            Some(scattered) => scattered,
+            None => return,
+        };
+        let scale = if from_camera {
+            1.
+        } else {
+            material.light_scale(hit_rec, &scattered)
+        };
+        let pdf_rev = if current.delta {
+            pdf_fwd = 0.;
+            0.
+        } else {
+            pdf_fwd = material.pdf(hit_rec, &scattered.direction());
+            material.pdf(hit_rec, &-ray.direction())
+        };
+        let pdf_rev = current.convert_density(pdf_rev, &path[previous]);
+        path[previous].pdf_rev = pdf_rev;
+
+        beta = scale * (beta * material.attenuation());
+        ray = scattered;
+
+        let bounces = (path.len() - 1) as u32;
+        if !russian_roulette(&mut beta, bounces, roulette_bounces, sampler) {
+            return;
+        }
+    }
+}
+
+fn camera_path(
+    r: &Ray,
+    camera: &dyn Camera,
+    scene: &HitList,
+    sampler: &mut dyn Sampler,
+    max_vertices: usize,
+    roulette_bounces: Option<u32>,
+) -> Vec<Vertex> {
+    let mut path = Vec::with_capacity(max_vertices);
+    let pdf = camera.importance_pdf(r);
+    // Cameras that light tracing cannot reach never connect to the lens.
+    path.push(Vertex::camera(r.origin(), pdf.is_none()));
+    let pdf_dir = pdf.map_or(0., |(_, pdf_dir)| pdf_dir);
+    random_walk(
+        scene,
+        sampler,
+        *r,
+        Vec3::new(1., 1., 1.),
+        pdf_dir,
+        max_vertices,
+        roulette_bounces,
+        &mut path,
+    );
+    path
+}
+
+// Always takes one 1D and two 2D dimensions.
+fn light_path(
+    scene: &HitList,
+    sampler: &mut dyn Sampler,
+    max_vertices: usize,
+    roulette_bounces: Option<u32>,
+) -> Vec<Vertex> {
+    let pick = sampler.get_1d();
+    let u_position = sampler.get_2d();
+    let u_direction = sampler.get_2d();
+    let count = light_count(scene);
+    if count == 0 || max_vertices == 0 {
+        return Vec::new();
+    }
+
+    // Picked like `sample_indexed_light` does, past the sphere lights is the
+    // environment.
+    let index = ((pick * count as f64) as usize).min(count - 1);
+    let light = match scene.lights.get(index) {
+        Some(light) => light,
+        None => return Vec::new(),
+    };
+    let normal = warp::uniform_sphere(u_position);
+    let point = light.center + light.radius * normal;
+    let pdf_position = 1. / (count as f64 * 4. * PI * light.radius * light.radius);
+    let direction = warp::cosine_hemisphere(&normal, u_direction);
+    let pdf_direction = Vec3::dot(&normal, &direction).max(0.) / PI;
+    if pdf_direction == 0. {
+        return Vec::new();
+    }
+
+    let mut path = Vec::with_capacity(max_vertices);
+    path.push(Vertex {
+        kind: Kind::Light(index),
+        point,
+        normal,
+        hit: None,
+        beta: light.emission / pdf_position,
+        pdf_fwd: pdf_position,
+        pdf_rev: 0.,
+        delta: false,
+    });
+    // The cosine of the emission cancels against the direction's density.
+    let beta = PI * light.emission / pdf_position;
+    random_walk(
+        scene,
+        sampler,
+        Ray::new(point, direction),
+        beta,
+        pdf_direction,
+        max_vertices,
+        roulette_bounces,
+        &mut path,
+    );
+    path
+}
+
+// Densities and flag of one vertex as seen by `mis_weight`.
+#[derive(Copy, Clone)]
+struct Densities {
+    fwd: f64,
+    rev: f64,
+    delta: bool,
+}
+
+impl Densities {
+    fn of(vertex: &Vertex) -> Densities {
+        Densities {
+            fwd: vertex.pdf_fwd,
+            rev: vertex.pdf_rev,
+            delta: vertex.delta,
+        }
+    }
+
+    // How much likelier the vertex is found from the other side, given
+    // whether the vertices it is found from on either side are specular.
+    // Densities out of a specular vertex are left out as every strategy
+    // goes through it the same way. Any other zero density means that side
+    // never finds the vertex.
+    fn ratio(&self, fwd_delta: bool, rev_delta: bool) -> f64 {
+        let through = |pdf: f64, delta: bool| if pdf == 0. && delta { 1. } else { pdf };
+        let fwd = through(self.fwd, fwd_delta);
+        if fwd == 0. {
+            0.
+        } else {
+            through(self.rev, rev_delta) / fwd
+        }
+    }
+}
+
+impl BdptIntegrator {
+    // Power heuristic weight of joining the first `s` light vertices to the
+    // first `t` camera vertices, against all other ways to build the same
+    // path. `sampled` is the vertex picked on the lens for `t` of 1, or
+    // else on a light for `s` of 1.
+    #[allow(clippy::too_many_arguments)]
+    fn mis_weight(
+        scene: &HitList,
+        camera: &dyn Camera,
+        light: &[Vertex],
+        eye: &[Vertex],
+        sampled: Option<&Vertex>,
+        s: usize,
+        t: usize,
+    ) -> f64 {
+        let (pt, qs) = match (s, t) {
+            (_, 1) => (sampled.unwrap(), light.get(s - 1)),
+            (0, _) => (&eye[t - 1], None),
+            (1, _) => (&eye[t - 1], sampled),
+            _ => (&eye[t - 1], Some(&light[s - 1])),
+        };
+        if s == 0 && (pt.pdf_light_origin(scene) == 0. || (t == 2 && pt.kind == Kind::Environment))
+        {
+            // Light no light path starts from, or that cannot be traced to
+            // the lens.
+            return 1.;
+        }
+
+        let mut eye_pdfs: Vec<Densities> = eye[..t].iter().map(Densities::of).collect();
+        let mut light_pdfs: Vec<Densities> = light.iter().take(s).map(Densities::of).collect();
+        eye_pdfs[t - 1] = Densities::of(pt);
+        if let Some(qs) = qs {
+            light_pdfs.truncate(s - 1);
+            light_pdfs.push(Densities::of(qs));
+        }
+
+        // The connected vertices are not specular, or they could not be
+        // connected, and get the densities of the other side.
+        eye_pdfs[t - 1].delta = false;
+        eye_pdfs[t - 1].rev = match qs {
+            Some(qs) => qs.pdf(scene, camera, pt),
+            None => pt.pdf_light_origin(scene),
+        };
+        if t > 1 {
+            eye_pdfs[t - 2].rev = match qs {
+                Some(_) => pt.pdf(scene, camera, &eye[t - 2]),
+                None => pt.pdf_light(scene, &eye[t - 2]),
+            };
+        }
+        if let Some(qs) = qs {
+            light_pdfs[s - 1].delta = false;
+            light_pdfs[s - 1].rev = pt.pdf(scene, camera, qs);
+            if s > 1 {
+                light_pdfs[s - 2].rev = qs.pdf(scene, camera, &light[s - 2]);
+            }
+        }
+
+        // Ratios of the densities of the other strategies to this one's,
+        // moving the connection along the camera and the light path.
+        let mut sum = 0.;
+        let mut ratio = 1.;
+        for i in (1..t).rev() {
+            let rev_delta = i + 1 < t && eye_pdfs[i + 1].delta;
+            ratio *= eye_pdfs[i].ratio(eye_pdfs[i - 1].delta, rev_delta);
+            if !eye_pdfs[i].delta && !eye_pdfs[i - 1].delta {
+                sum += ratio * ratio;
+            }
+        }
+        ratio = 1.;
+        for i in (0..s).rev() {
+            let delta_before = i > 0 && light_pdfs[i - 1].delta;
+            let rev_delta = i + 1 < s && light_pdfs[i + 1].delta;
+            ratio *= light_pdfs[i].ratio(delta_before, rev_delta);
+            if !light_pdfs[i].delta && !delta_before {
+                sum += ratio * ratio;
+            }
+        }
+        1. / (1. + sum)
+    }
+
+    // Radiance of the path joining `s` light and `t` camera vertices,
+    // light traced to the lens goes to `splat` instead.
+    #[allow(clippy::too_many_arguments)]
+    fn connect(
+        scene: &HitList,
+        camera: &dyn Camera,
+        light: &[Vertex],
+        eye: &[Vertex],
+        s: usize,
+        t: usize,
+        sampler: &mut dyn Sampler,
+        splat: &mut dyn FnMut((f64, f64), Radiance),
+    ) -> Vec3 {
+        let pt = &eye[t - 1];
+        if s == 0 {
+            let radiance = pt.beta * pt.emitted(scene);
+            if is_black(&radiance) {
+                return radiance;
+            }
+            return BdptIntegrator::mis_weight(scene, camera, light, eye, None, s, t) * radiance;
+        }
+
+        if t == 1 {
+            let qs = &light[s - 1];
+            let u = sampler.get_2d();
+            if !qs.is_connectible() {
+                return Vec3::zero();
+            }
+            let sample = match camera.sample_importance(&qs.point, u) {
+                Some(sample) => sample,
+                None => return Vec3::zero(),
+            };
+            let to_lens = sample.lens_point - qs.point;
+            let radiance = sample.weight * (qs.beta * qs.f(&to_lens));
+            if is_black(&radiance) || !unoccluded(scene, &qs.point, &to_lens, to_lens.length()) {
+                return Vec3::zero();
+            }
+
+            let lens = Vertex::camera(sample.lens_point, false);
+            let weight = BdptIntegrator::mis_weight(scene, camera, light, eye, Some(&lens), s, t);
+            // The camera sees the light vertex first, lights are diffuse.
+            let mut splatted = Radiance {
+                lobe: match (&qs.hit, qs.kind) {
+                    (Some(hit), _) => Some(hit.material.lobe()),
+                    (None, Kind::Light(_)) => Some(Lobe::Diffuse),
+                    _ => None,
+                },
+                ..Radiance::zero()
+            };
+            splatted.add((s - 1) as u32, weight * radiance);
+            splat(sample.film_position, splatted);
+            return Vec3::zero();
+        }
+
+        if s == 1 {
+            // Light sampling, like the path tracers do it.
+            let sample = sample_indexed_light(scene, &pt.point, sampler);
+            if !pt.is_connectible() {
+                return Vec3::zero();
+            }
+            let (index, sample) = match sample {
+                Some((index, sample)) if sample.pdf > 0. => (index, sample),
+                _ => return Vec3::zero(),
+            };
+            let radiance = (pt.beta * pt.f(&sample.direction) * sample.radiance) / sample.pdf;
+            if is_black(&radiance)
+                || !unoccluded(scene, &pt.point, &sample.direction, sample.distance)
+            {
+                return Vec3::zero();
+            }
+
+            let mut on_light = match index {
+                Some(index) => {
+                    let light = &scene.lights[index];
+                    let point = pt.point + sample.distance * sample.direction;
+                    Vertex {
+                        kind: Kind::Light(index),
+                        point,
+                        normal: (point - light.center) / light.radius,
+                        hit: None,
+                        beta: Vec3::zero(),
+                        pdf_fwd: 0.,
+                        pdf_rev: 0.,
+                        delta: false,
+                    }
+                }
+                None => Vertex {
+                    kind: Kind::Environment,
+                    point: sample.direction,
+                    normal: Vec3::zero(),
+                    hit: None,
+                    beta: Vec3::zero(),
+                    pdf_fwd: 0.,
+                    pdf_rev: 0.,
+                    delta: false,
+                },
+            };
+            on_light.pdf_fwd = on_light.pdf_light_origin(scene);
+            let weight =
+                BdptIntegrator::mis_weight(scene, camera, light, eye, Some(&on_light), s, t);
+            return weight * radiance;
+        }
+
+        let qs = &light[s - 1];
+        if !pt.is_connectible() || !qs.is_connectible() {
+            return Vec3::zero();
+        }
+        let d = qs.point - pt.point;
+        let distance_sq = Vec3::dot(&d, &d);
+        let radiance = (qs.beta * qs.f(&-d) * pt.f(&d) * pt.beta) / distance_sq;
+        if is_black(&radiance) || !unoccluded(scene, &pt.point, &d, distance_sq.sqrt()) {
+            return Vec3::zero();
+        }
+        BdptIntegrator::mis_weight(scene, camera, light, eye, None, s, t) * radiance
+    }
+}
+
+impl Integrator for BdptIntegrator {
+    fn name(&self) -> &'static str {
+        "bdpt"
+    }
+
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        // Without a camera to trace light to, the light paths only reach
+        // the film through the camera paths.
+        struct NoCamera;
+        impl Camera for NoCamera {
+            fn send_ray(&self, _s: f64, _t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+                None
+            }
+        }
+        self.li_splat(r, &NoCamera, scene, sampler, depth, &mut |_, _| {})
+    }
+
+    fn li_splat(
+        &self,
+        r: &Ray,
+        camera: &dyn Camera,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+        splat: &mut dyn FnMut((f64, f64), Radiance),
+    ) -> (Radiance, u32) {
+        // A path of `depth` rays has `depth + 1` vertices, the camera's
+        // included. Light paths hold one less, every strategy takes at
+        // least the lens from the camera side.
+        let depth = depth as usize;
+        let eye = camera_path(r, camera, scene, sampler, depth + 1, self.roulette_bounces);
+        let light = light_path(scene, sampler, depth, self.roulette_bounces);
+
+        let mut radiance = Radiance {
+            lobe: eye
+                .get(1)
+                .and_then(|vertex| vertex.hit.as_ref())
+                .map(|hit| hit.material.lobe()),
+            ..Radiance::zero()
+        };
+        for t in 1..=eye.len() {
+            // Light sampling picks its own vertex on a light, also when no
+            // light path could be traced.
+            for s in 0..=light.len().max(1) {
+                if s + t < 2 || s + t - 1 > depth || (s > light.len() && t == 1) {
+                    continue;
+                }
+                // Every vertex between the lens and the light scatters.
+                radiance.add(
+                    (s + t - 2) as u32,
+                    BdptIntegrator::connect(scene, camera, &light, &eye, s, t, sampler, splat),
+                );
+            }
+        }
+        (radiance, (eye.len() - 1) as u32)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::integrator::{Integrator, MisPathIntegrator};
+use crate::sampler::Sampler;
+use crate::structs::film::Radiance;
+use crate::structs::hitable::HitList;
+use crate::structs::ray::Ray;
+
+/// Light reaching the camera straight from the lights or after a single
+/// bounce, sampled like `MisPathIntegrator` does. Shows what the lights do
+/// on their own, without any indirect light.
+#[derive(Debug, Clone)]
+pub struct DirectLightingIntegrator {
+    path: MisPathIntegrator,
+}
+
+impl DirectLightingIntegrator {
+    pub fn new() -> DirectLightingIntegrator {
+        DirectLightingIntegrator {
+            path: MisPathIntegrator::new().with_russian_roulette(None),
+        }
+    }
+}
+
+impl Default for DirectLightingIntegrator {
+    fn default() -> DirectLightingIntegrator {
+        DirectLightingIntegrator::new()
+    }
+}
+
+impl Integrator for DirectLightingIntegrator {
+    fn name(&self) -> &'static str {
+        "direct"
+    }
+
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        // The camera ray and the one leaving the first bounce.
+        self.path.li(r, scene, sampler, depth.min(2))
+    }
+}
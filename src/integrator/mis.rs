@@ -0,0 +1,129 @@
+use crate::integrator::{
+    is_black, light_pdf, power_heuristic, russian_roulette, sample_light, trace, unoccluded,
+    Integrator,
+};
+use crate::sampler::Sampler;
+use crate::stats;
+use crate::structs::film::Radiance;
+use crate::structs::hitable::HitList;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+/// Path tracer with next event estimation: at every diffuse bounce it also
+/// sends a shadow ray to a light. Light found that way and light found by
+/// following the materials are combined with multiple importance sampling,
+/// so small bright lights and broad ones both converge quickly.
+#[derive(Debug, Clone)]
+pub struct MisPathIntegrator {
+    roulette_bounces: Option<u32>,
+}
+
+impl MisPathIntegrator {
+    pub fn new() -> MisPathIntegrator {
+        MisPathIntegrator {
+            roulette_bounces: Some(3),
+        }
+    }
+
+    /// Bounces after which paths are ended by Russian roulette, `None`
+    /// follows every path to the ray depth.
+    pub fn with_russian_roulette(mut self, min_bounces: Option<u32>) -> MisPathIntegrator {
+        self.roulette_bounces = min_bounces;
+        self
+    }
+}
+
+impl Default for MisPathIntegrator {
+    fn default() -> MisPathIntegrator {
+        MisPathIntegrator::new()
+    }
+}
+
+impl Integrator for MisPathIntegrator {
+    fn name(&self) -> &'static str {
+        "path_mis"
+    }
+
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        let mut ray = *r;
+        let mut radiance = Radiance::zero();
+        let mut throughput = Vec3::new(1., 1., 1.);
+        let mut bounces = 0;
+        // Where the last bounce happened and the density it picked `ray`
+        // with, `None` for the camera and specular bounces, which light
+        // sampling cannot reproduce.
+        let mut previous: Option<(Point3, f64)> = None;
+
+        loop {
+            if bounces == depth {
+                stats::record(|stats| stats.terminated_by_depth += 1);
+                return (radiance, bounces);
+            }
+
+            let hit_rec = trace(scene, &ray);
+            let emitted = match &hit_rec {
+                Some(hit_rec) => hit_rec.material.emitted(hit_rec),
+                None => scene.environment.radiance(&ray.direction()),
+            };
+            if !is_black(&emitted) {
+                let weight = match previous {
+                    Some((point, pdf)) => power_heuristic(
+                        pdf,
+                        light_pdf(scene, &point, &ray.direction(), hit_rec.as_ref()),
+                    ),
+                    None => 1.,
+                };
+                radiance.add(bounces, weight * (throughput * emitted));
+            }
+            let hit_rec = match hit_rec {
+                Some(hit_rec) => hit_rec,
+                None => return (radiance, bounces),
+            };
+            if bounces == 0 {
+                radiance.lobe = Some(hit_rec.material.lobe());
+            }
+            bounces += 1;
+            let material = hit_rec.material;
+
+            // Only where the path goes on, so light sampling is always
+            // matched by the ray found by following the material.
+            if !material.is_specular() && bounces < depth {
+                if let Some(light) = sample_light(scene, &hit_rec.hit_point, sampler) {
+                    let f = material.eval(&hit_rec, &light.direction);
+                    if light.pdf > 0.
+                        && !is_black(&f)
+                        && unoccluded(scene, &hit_rec.hit_point, &light.direction, light.distance)
+                    {
+                        let weight =
+                            power_heuristic(light.pdf, material.pdf(&hit_rec, &light.direction));
+                        radiance.add(
+                            bounces,
+                            (weight / light.pdf) * (throughput * f * light.radiance),
+                        );
+                    }
+                }
+            }
+
+            ray = match material.scatter(&ray, &hit_rec, sampler) {
+                Some(scattered) => scattered,
+                None => return (radiance, bounces),
+            };
+            previous = if material.is_specular() {
+                None
+            } else {
+                Some((hit_rec.hit_point, material.pdf(&hit_rec, &ray.direction())))
+            };
+            throughput = throughput * material.attenuation();
+
+            if !russian_roulette(&mut throughput, bounces, self.roulette_bounces, sampler) {
+                return (radiance, bounces);
+            }
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use crate::integrator::{Integrator, MisPathIntegrator};
+use crate::output::tonemap::luminance;
+use crate::sampler::{IndependentSampler, Sampler};
+use crate::stats;
+use crate::structs::camera::Camera;
+use crate::structs::film::Radiance;
+use crate::structs::hitable::HitList;
+use crate::structs::ray::Ray;
+
+use rayon::prelude::*;
+use std::f64::consts::PI;
+use std::sync::{Arc, RwLock};
+
+// Bootstrap paths traced by one task.
+const BOOTSTRAP_CHUNK: u32 = 4096;
+
+// Row past the image the random numbers of the bootstrap paths are drawn
+// for, apart from the camera samples and the photons.
+const BOOTSTRAP_ROW: u32 = u32::MAX - 1;
+
+/// Primary sample space Metropolis light transport, after Kelemen et al.
+/// 2002. A path is the one `path_mis` traces from a list of random numbers,
+/// film position included, and Markov chains wander through those lists by
+/// nudging all numbers a little or, with `large_step_probability`, drawing
+/// them anew. Chains stay where much light arrives, so light coming in
+/// through small gaps is found again once it was found once.
+///
+/// Every camera sample runs one chain of `mutations` steps that starts at
+/// one of the bootstrap paths traced before the pass, picked by how bright
+/// it is. Their mean brightness scales the image, the light of every step
+/// is splatted wherever on the film it lands.
+pub struct MetropolisIntegrator {
+    path: MisPathIntegrator,
+    mutations: u32,
+    bootstrap_paths: u32,
+    sigma: f64,
+    large_step_probability: f64,
+    bootstrap: RwLock<Option<Arc<Bootstrap>>>,
+}
+
+impl MetropolisIntegrator {
+    pub fn new() -> MetropolisIntegrator {
+        MetropolisIntegrator {
+            path: MisPathIntegrator::new(),
+            mutations: 100,
+            bootstrap_paths: 100_000,
+            sigma: 0.01,
+            large_step_probability: 0.3,
+            bootstrap: RwLock::new(None),
+        }
+    }
+
+    /// Steps of the chain every camera sample runs.
+    pub fn with_mutations(mut self, mutations: u32) -> MetropolisIntegrator {
+        self.mutations = mutations;
+        self
+    }
+
+    /// Paths traced before every pass to estimate the brightness of the
+    /// image and pick where chains start.
+    pub fn with_bootstrap_paths(mut self, paths: u32) -> MetropolisIntegrator {
+        self.bootstrap_paths = paths;
+        self
+    }
+
+    /// Standard deviation of the small steps, in primary sample space where
+    /// every random number lies in [0, 1).
+    pub fn with_sigma(mut self, sigma: f64) -> MetropolisIntegrator {
+        self.sigma = sigma;
+        self
+    }
+
+    /// Chance that a step draws all random numbers anew instead.
+    pub fn with_large_step_probability(mut self, probability: f64) -> MetropolisIntegrator {
+        self.large_step_probability = probability;
+        self
+    }
+
+    /// Bounces after which the paths are ended by Russian roulette, `None`
+    /// follows every path to the ray depth.
+    pub fn with_russian_roulette(mut self, min_bounces: Option<u32>) -> MetropolisIntegrator {
+        self.path = self.path.with_russian_roulette(min_bounces);
+        self
+    }
+
+    // Film position and radiance of the path `samples` describe.
+    fn evaluate(
+        &self,
+        camera: &dyn Camera,
+        scene: &HitList,
+        samples: &mut PrimarySamples,
+        depth: u32,
+    ) -> ((f64, f64), Radiance) {
+        let (s, t) = samples.get_2d();
+        stats::record(|stats| stats.camera_rays += 1);
+        let radiance = match camera.send_weighted_ray(s, t, samples) {
+            Some((r, weight)) => weight * self.path.li(&r, scene, samples, depth).0,
+            None => Radiance::zero(),
+        };
+        ((s, t), radiance)
+    }
+}
+
+impl Default for MetropolisIntegrator {
+    fn default() -> MetropolisIntegrator {
+        MetropolisIntegrator::new()
+    }
+}
+
+impl Integrator for MetropolisIntegrator {
+    fn name(&self) -> &'static str {
+        "mlt"
+    }
+
+    // Without a camera to start chains from, this is plain path tracing.
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        self.path.li(r, scene, sampler, depth)
+    }
+
+    fn li_splat(
+        &self,
+        _r: &Ray,
+        camera: &dyn Camera,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+        splat: &mut dyn FnMut((f64, f64), Radiance),
+    ) -> (Radiance, u32) {
+        let bounces = self.splat(camera, scene, sampler, depth, splat);
+        (Radiance::zero(), bounces)
+    }
+
+    fn splats_only(&self) -> bool {
+        true
+    }
+
+    fn splat(
+        &self,
+        camera: &dyn Camera,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+        splat: &mut dyn FnMut((f64, f64), Radiance),
+    ) -> u32 {
+        let pick = sampler.get_1d();
+        let chain_seed = ((sampler.get_1d() * 2f64.powi(32)) as u64) << 32
+            | (sampler.get_1d() * 2f64.powi(32)) as u64;
+        let bootstrap = match self.bootstrap.read().unwrap().clone() {
+            Some(bootstrap) if bootstrap.mean > 0. => bootstrap,
+            _ => return 0,
+        };
+
+        let mut samples = PrimarySamples::new(
+            (bootstrap.seed, bootstrap.pass),
+            bootstrap.pick(pick),
+            chain_seed,
+            self.sigma,
+            self.large_step_probability,
+        );
+        let (mut position, mut radiance) = self.evaluate(camera, scene, &mut samples, depth);
+        let mut brightness = luminance(&radiance.total());
+        let scale = bootstrap.mean / self.mutations as f64;
+
+        for _ in 0..self.mutations {
+            samples.start_iteration();
+            let (proposed_position, proposed) = self.evaluate(camera, scene, &mut samples, depth);
+            let proposed_brightness = luminance(&proposed.total());
+            let accept = if brightness > 0. {
+                (proposed_brightness / brightness).min(1.)
+            } else {
+                1.
+            };
+
+            // Both paths are splatted by how likely the chain moves on to
+            // them, which lowers the noise over just the path it is at.
+            if proposed_brightness > 0. {
+                splat(
+                    proposed_position,
+                    (accept * scale / proposed_brightness) * proposed,
+                );
+            }
+            if brightness > 0. {
+                splat(position, ((1. - accept) * scale / brightness) * radiance);
+            }
+
+            if samples.uniform() < accept {
+                position = proposed_position;
+                radiance = proposed;
+                brightness = proposed_brightness;
+                samples.accept();
+            } else {
+                samples.reject();
+            }
+        }
+        0
+    }
+
+    // A new bootstrap every sample, its normalization is averaged over them.
+    fn pass_samples(&self) -> Option<u32> {
+        Some(1)
+    }
+
+    fn prepare(
+        &self,
+        scene: &HitList,
+        camera: &(dyn Camera + Sync),
+        seed: u64,
+        samples: u32,
+        depth: u32,
+    ) {
+        let chunks = self.bootstrap_paths.div_ceil(BOOTSTRAP_CHUNK);
+        let brightness: Vec<f64> = (0..chunks)
+            .into_par_iter()
+            .map(|chunk| {
+                let first = chunk * BOOTSTRAP_CHUNK;
+                (first..(first + BOOTSTRAP_CHUNK).min(self.bootstrap_paths))
+                    .map(|index| {
+                        let mut path = PrimarySamples::new((seed, samples), index, 0, 0., 0.);
+                        luminance(&self.evaluate(camera, scene, &mut path, depth).1.total())
+                    })
+                    .collect::<Vec<f64>>()
+            })
+            .flatten()
+            .collect();
+
+        let mut total = 0.;
+        let cdf: Vec<f64> = brightness
+            .iter()
+            .map(|brightness| {
+                total += brightness;
+                total
+            })
+            .collect();
+        let bootstrap = Bootstrap {
+            seed,
+            pass: samples,
+            mean: total / brightness.len().max(1) as f64,
+            cdf,
+        };
+        *self.bootstrap.write().unwrap() = Some(Arc::new(bootstrap));
+    }
+}
+
+// Paths traced before a pass, chains start at one of them.
+struct Bootstrap {
+    // Seed and pass their random numbers were drawn for.
+    seed: u64,
+    pass: u32,
+    // Mean luminance of the paths, the brightness of the whole image.
+    mean: f64,
+    // Running sum of the luminance of the paths.
+    cdf: Vec<f64>,
+}
+
+impl Bootstrap {
+    // Index of a path, picked in proportion to its luminance.
+    fn pick(&self, u: f64) -> u32 {
+        let total = self.cdf.last().copied().unwrap_or(0.);
+        let index = self.cdf.partition_point(|sum| *sum <= u * total);
+        index.min(self.cdf.len().saturating_sub(1)) as u32
+    }
+}
+
+#[derive(Copy, Clone)]
+struct PrimarySample {
+    value: f64,
+    // Iteration the value is up to date with.
+    modified: u64,
+    // Value and iteration to return to when a step is rejected.
+    backup: (f64, u64),
+}
+
+// Random numbers of a chain's current path, handed out in order as a
+// sampler. Numbers are only brought up to date with the steps the chain
+// took when they are asked for, so paths may use any number of them.
+#[derive(Clone)]
+struct PrimarySamples {
+    values: Vec<PrimarySample>,
+    // Numbers a path asks for the first time, the n-th draw is number n,
+    // so the starting path of a chain is the bootstrap path it picked.
+    fresh: IndependentSampler,
+    // Everything the chain decides.
+    rng: IndependentSampler,
+    sigma: f64,
+    large_step_probability: f64,
+    iteration: u64,
+    large_step: bool,
+    last_large_step: u64,
+    dimension: usize,
+}
+
+impl PrimarySamples {
+    fn new(
+        (seed, pass): (u64, u32),
+        index: u32,
+        chain_seed: u64,
+        sigma: f64,
+        large_step_probability: f64,
+    ) -> PrimarySamples {
+        let mut fresh = IndependentSampler::new();
+        fresh.set_seed(seed);
+        fresh.start_pixel_sample((pass, BOOTSTRAP_ROW), index, 0);
+        let mut rng = IndependentSampler::new();
+        rng.set_seed(chain_seed);
+        rng.start_pixel_sample((0, 0), 0, 0);
+        PrimarySamples {
+            values: Vec::new(),
+            fresh,
+            rng,
+            sigma,
+            large_step_probability,
+            iteration: 0,
+            large_step: false,
+            last_large_step: 0,
+            dimension: 0,
+        }
+    }
+
+    fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = self.rng.get_1d() < self.large_step_probability;
+        self.dimension = 0;
+    }
+
+    fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step = self.iteration;
+        }
+    }
+
+    fn reject(&mut self) {
+        for sample in &mut self.values {
+            if sample.modified == self.iteration {
+                (sample.value, sample.modified) = sample.backup;
+            }
+        }
+        self.iteration -= 1;
+    }
+
+    fn uniform(&mut self) -> f64 {
+        self.rng.get_1d()
+    }
+
+    // Number `index` after the steps taken since it was last used.
+    fn ensure_ready(&mut self, index: usize) -> f64 {
+        while self.values.len() <= index {
+            let value = self.fresh.get_1d();
+            self.values.push(PrimarySample {
+                value,
+                modified: self.iteration,
+                backup: (value, self.iteration),
+            });
+        }
+
+        let rng = &mut self.rng;
+        let sample = &mut self.values[index];
+        if sample.modified < self.last_large_step {
+            sample.value = rng.get_1d();
+            sample.modified = self.last_large_step;
+        }
+        sample.backup = (sample.value, sample.modified);
+
+        if self.large_step {
+            sample.value = rng.get_1d();
+        } else {
+            let steps = self.iteration.saturating_sub(sample.modified);
+            if steps > 0 {
+                // Several small steps at once are one with their variances added.
+                let (u1, u2) = rng.get_2d();
+                let normal = (-2. * (1. - u1).ln()).sqrt() * (2. * PI * u2).cos();
+                let value = sample.value + normal * self.sigma * (steps as f64).sqrt();
+                sample.value = value - value.floor();
+                if sample.value >= 1. {
+                    sample.value = 0.;
+                }
+            }
+        }
+        sample.modified = self.iteration;
+        sample.value
+    }
+}
+
+impl Sampler for PrimarySamples {
+    fn name(&self) -> &'static str {
+        "primary_sample_space"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng.set_seed(seed);
+    }
+
+    fn start_pixel_sample(&mut self, _pixel: (u32, u32), _index: u32, _count: u32) {
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let value = self.ensure_ready(self.dimension);
+        self.dimension += 1;
+        value
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.get_1d(), self.get_1d())
+    }
+
+    fn box_clone(&self) -> Box<dyn Sampler + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
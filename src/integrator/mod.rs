@@ -0,0 +1,293 @@
+mod ao;
+mod bdpt;
+mod direct;
+mod mis;
+mod mlt;
+mod path;
+mod photon;
+mod whitted;
+
+pub use ao::AmbientOcclusionIntegrator;
+pub use bdpt::BdptIntegrator;
+pub use direct::DirectLightingIntegrator;
+pub use mis::MisPathIntegrator;
+pub use mlt::MetropolisIntegrator;
+pub use path::PathIntegrator;
+pub use photon::PhotonMappingIntegrator;
+pub use whitted::WhittedIntegrator;
+
+use crate::sampler::{warp, Sampler};
+use crate::stats;
+use crate::structs::camera::Camera;
+use crate::structs::film::Radiance;
+use crate::structs::hitable::{HitList, HitRecord, Hitable};
+use crate::structs::light::LightSample;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+use std::f64::consts::PI;
+
+/// Light transport algorithm, finds the light arriving at the camera along
+/// camera rays.
+pub trait Integrator {
+    fn name(&self) -> &'static str;
+
+    /// Radiance arriving along `r` and the number of surfaces the path
+    /// bounced off, tracing at most `depth` rays one after another.
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32);
+
+    /// `li` for integrators that also trace light from the lights to the
+    /// camera. Light reaching the film that way can land on any pixel, it
+    /// goes to `splat` with its film position `(s, t)`.
+    fn li_splat(
+        &self,
+        r: &Ray,
+        _camera: &dyn Camera,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+        _splat: &mut dyn FnMut((f64, f64), Radiance),
+    ) -> (Radiance, u32) {
+        self.li(r, scene, sampler, depth)
+    }
+
+    /// Whether light only reaches the film through `splat`, `li_splat`
+    /// then always returns black. Such integrators get no camera ray and
+    /// adaptive sampling has no noise to measure for them.
+    fn splats_only(&self) -> bool {
+        false
+    }
+
+    /// `li_splat` without a camera ray, for integrators that splat only.
+    /// Returns the number of surfaces the paths bounced off.
+    fn splat(
+        &self,
+        _camera: &dyn Camera,
+        _scene: &HitList,
+        _sampler: &mut dyn Sampler,
+        _depth: u32,
+        _splat: &mut dyn FnMut((f64, f64), Radiance),
+    ) -> u32 {
+        0
+    }
+
+    /// Most samples per pixel to take between two calls to `prepare`,
+    /// `None` if any number will do.
+    fn pass_samples(&self) -> Option<u32> {
+        None
+    }
+
+    /// Called before every pass over the image with the render seed, the
+    /// most samples any pixel has so far and the ray depth, for integrators
+    /// that first trace something all pixels share.
+    fn prepare(
+        &self,
+        _scene: &HitList,
+        _camera: &(dyn Camera + Sync),
+        _seed: u64,
+        _samples: u32,
+        _depth: u32,
+    ) {
+    }
+}
+
+/// Settings of the integrators `by_name` makes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IntegratorOptions {
+    /// Bounces after which path tracers end paths by Russian roulette.
+    pub roulette_bounces: Option<u32>,
+    /// How far ambient occlusion looks for occluders.
+    pub ao_distance: f64,
+    /// Photons photon mapping shoots before every pass.
+    pub photons: u32,
+    /// Gather radius photon mapping starts with.
+    pub photon_radius: f64,
+    /// Steps of the Markov chain every camera sample runs with Metropolis
+    /// light transport.
+    pub mlt_mutations: u32,
+    /// Paths Metropolis light transport traces before every pass to
+    /// normalize the image and start its chains.
+    pub mlt_bootstrap: u32,
+    /// Size of the small Metropolis steps in primary sample space.
+    pub mlt_sigma: f64,
+    /// Chance of a Metropolis step drawing a new path from scratch.
+    pub mlt_large_step: f64,
+}
+
+impl Default for IntegratorOptions {
+    fn default() -> IntegratorOptions {
+        IntegratorOptions {
+            roulette_bounces: Some(3),
+            ao_distance: 1.,
+            photons: 100_000,
+            photon_radius: 0.1,
+            mlt_mutations: 100,
+            mlt_bootstrap: 100_000,
+            mlt_sigma: 0.01,
+            mlt_large_step: 0.3,
+        }
+    }
+}
+
+pub fn by_name(
+    name: &str,
+    options: &IntegratorOptions,
+) -> Option<Box<dyn Integrator + Send + Sync>> {
+    let integrator: Box<dyn Integrator + Send + Sync> = match name.to_ascii_lowercase().as_str() {
+        "path" | "naive" => {
+            Box::new(PathIntegrator::new().with_russian_roulette(options.roulette_bounces))
+        }
+        "path_mis" | "nee" | "mis" => {
+            Box::new(MisPathIntegrator::new().with_russian_roulette(options.roulette_bounces))
+        }
+        "ao" | "ambient_occlusion" => {
+            Box::new(AmbientOcclusionIntegrator::new(options.ao_distance))
+        }
+        "direct" => Box::new(DirectLightingIntegrator::new()),
+        "whitted" => Box::new(WhittedIntegrator::new()),
+        "bdpt" | "bidirectional" => {
+            Box::new(BdptIntegrator::new().with_russian_roulette(options.roulette_bounces))
+        }
+        "photon" | "ppm" | "sppm" => Box::new(
+            PhotonMappingIntegrator::new()
+                .with_photons(options.photons)
+                .with_radius(options.photon_radius)
+                .with_russian_roulette(options.roulette_bounces),
+        ),
+        "mlt" | "pssmlt" | "metropolis" => Box::new(
+            MetropolisIntegrator::new()
+                .with_mutations(options.mlt_mutations)
+                .with_bootstrap_paths(options.mlt_bootstrap)
+                .with_sigma(options.mlt_sigma)
+                .with_large_step_probability(options.mlt_large_step)
+                .with_russian_roulette(options.roulette_bounces),
+        ),
+        _ => return None,
+    };
+    Some(integrator)
+}
+
+// Closest hit along `r`, counted as a ray.
+fn trace(scene: &HitList, r: &Ray) -> Option<HitRecord> {
+    stats::record(|stats| stats.rays += 1);
+    scene.hit(r, 0.001, f64::MAX)
+}
+
+// Whether nothing blocks the way from `from` along `direction` for
+// `distance`, counted as a shadow ray.
+fn unoccluded(scene: &HitList, from: &Point3, direction: &Vec3, distance: f64) -> bool {
+    stats::record(|stats| {
+        stats.rays += 1;
+        stats.shadow_rays += 1;
+    });
+    let r = Ray::new(*from, Vec3::unit_vector(*direction));
+    scene.hit(&r, 0.001, distance * (1. - 1e-6)).is_none()
+}
+
+// Number of lights to pick from, the environment counts as one unless black.
+fn light_count(scene: &HitList) -> usize {
+    scene.lights.len() + !scene.environment.is_black() as usize
+}
+
+// Picks a light uniformly and a direction towards it, the pdf includes the
+// choice. Always takes one 1D and one 2D dimension.
+fn sample_light(scene: &HitList, p: &Point3, sampler: &mut dyn Sampler) -> Option<LightSample> {
+    sample_indexed_light(scene, p, sampler).map(|(_, sample)| sample)
+}
+
+// `sample_light`, also telling which of the scene's lights was picked,
+// `None` for the environment.
+fn sample_indexed_light(
+    scene: &HitList,
+    p: &Point3,
+    sampler: &mut dyn Sampler,
+) -> Option<(Option<usize>, LightSample)> {
+    let pick = sampler.get_1d();
+    let u = sampler.get_2d();
+    let count = light_count(scene);
+    if count == 0 {
+        return None;
+    }
+
+    let index = ((pick * count as f64) as usize).min(count - 1);
+    let sample = match scene.lights.get(index) {
+        Some(light) => light.sample(p, u)?,
+        None => {
+            let direction = warp::uniform_sphere(u);
+            LightSample {
+                direction,
+                distance: f64::INFINITY,
+                radiance: scene.environment.radiance(&direction),
+                pdf: 1. / (4. * PI),
+            }
+        }
+    };
+    Some((
+        scene.lights.get(index).map(|_| index),
+        LightSample {
+            pdf: sample.pdf / count as f64,
+            ..sample
+        },
+    ))
+}
+
+// Density of `sample_light` finding `direction` from `p` on the light hit
+// by it, `None` for the environment.
+fn light_pdf(scene: &HitList, p: &Point3, direction: &Vec3, hit: Option<&HitRecord>) -> f64 {
+    let count = light_count(scene);
+    if count == 0 {
+        return 0.;
+    }
+    let pdf = match hit {
+        Some(hit) => scene
+            .lights
+            .iter()
+            .find(|light| light.object_id == hit.object_id)
+            .map_or(0., |light| light.pdf(p, direction)),
+        None if scene.environment.is_black() => 0.,
+        None => 1. / (4. * PI),
+    };
+    pdf / count as f64
+}
+
+// Multiple importance sampling weight of a strategy with density `pdf`
+// against one with `other`, after Veach's power heuristic.
+fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b == 0. {
+        0.
+    } else {
+        a / (a + b)
+    }
+}
+
+fn is_black(v: &Vec3) -> bool {
+    v.x_ <= 0. && v.y_ <= 0. && v.z_ <= 0.
+}
+
+// Keeps a path going with a chance following its throughput, past
+// `min_bounces`. Survivors have their throughput weighted up by the
+// inverse, so the estimate stays unbiased.
+fn russian_roulette(
+    throughput: &mut Vec3,
+    bounces: u32,
+    min_bounces: Option<u32>,
+    sampler: &mut dyn Sampler,
+) -> bool {
+    if min_bounces.is_none_or(|min| bounces < min) {
+        return true;
+    }
+    let survival = throughput.x_.max(throughput.y_).max(throughput.z_).min(1.);
+    if sampler.get_1d() >= survival {
+        stats::record(|stats| stats.terminated_by_roulette += 1);
+        return false;
+    }
+    *throughput = *throughput / survival;
+    true
+}
@@ -0,0 +1,88 @@
+use crate::integrator::{russian_roulette, trace, Integrator};
+use crate::sampler::Sampler;
+use crate::stats;
+use crate::structs::film::Radiance;
+use crate::structs::hitable::HitList;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::Vec3;
+
+/// Plain path tracer: paths bounce where the materials scatter them and
+/// pick up light only when they happen to hit a light or leave the scene.
+///
+/// The path is followed in a loop carrying its throughput, the share of the
+/// light found further on that reaches the camera.
+#[derive(Debug, Clone)]
+pub struct PathIntegrator {
+    roulette_bounces: Option<u32>,
+}
+
+impl PathIntegrator {
+    pub fn new() -> PathIntegrator {
+        PathIntegrator {
+            roulette_bounces: Some(3),
+        }
+    }
+
+    /// Bounces after which paths are ended by Russian roulette, `None`
+    /// follows every path to the ray depth.
+    pub fn with_russian_roulette(mut self, min_bounces: Option<u32>) -> PathIntegrator {
+        self.roulette_bounces = min_bounces;
+        self
+    }
+}
+
+impl Default for PathIntegrator {
+    fn default() -> PathIntegrator {
+        PathIntegrator::new()
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn name(&self) -> &'static str {
+        "path"
+    }
+
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        let mut ray = *r;
+        let mut radiance = Radiance::zero();
+        let mut throughput = Vec3::new(1., 1., 1.);
+        let mut bounces = 0;
+
+        loop {
+            if bounces == depth {
+                stats::record(|stats| stats.terminated_by_depth += 1);
+                return (radiance, bounces);
+            }
+
+            let hit_rec = match trace(scene, &ray) {
+                Some(hit_rec) => hit_rec,
+                None => {
+                    let background = scene.environment.radiance(&ray.direction());
+                    radiance.add(bounces, throughput * background);
+                    return (radiance, bounces);
+                }
+            };
+            if bounces == 0 {
+                radiance.lobe = Some(hit_rec.material.lobe());
+            }
+            radiance.add(bounces, throughput * hit_rec.material.emitted(&hit_rec));
+            bounces += 1;
+
+            ray = match hit_rec.material.scatter(&ray, &hit_rec, sampler) {
+                Some(scattered) => scattered,
+                None => return (radiance, bounces),
+            };
+            throughput = throughput * hit_rec.material.attenuation();
+
+            if !russian_roulette(&mut throughput, bounces, self.roulette_bounces, sampler) {
+                return (radiance, bounces);
+            }
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use crate::integrator::{
+    is_black, light_pdf, power_heuristic, russian_roulette, sample_light, trace, unoccluded,
+    Integrator,
+};
+use crate::sampler::{warp, IndependentSampler, Sampler};
+use crate::stats;
+use crate::structs::camera::Camera;
+use crate::structs::film::Radiance;
+use crate::structs::hitable::{HitList, HitRecord, Hitable};
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::sync::{Arc, RwLock};
+
+// Photons traced by one task of the photon pass.
+const PHOTON_CHUNK: u32 = 4096;
+
+// Photons bouncing off mirrors and glass for longer are dropped.
+const MAX_SPECULAR_BOUNCES: u32 = 32;
+
+/// Stochastic progressive photon mapping for caustics. Before every pass
+/// photons are shot from the lights and the sky through mirrors and glass,
+/// and where they come to rest on a diffuse surface they are gathered by
+/// the camera paths, which are traced as with `path_mis` otherwise. The
+/// gather radius shrinks from pass to pass, so the blur of the caustics
+/// goes away as the image converges, after Knaus and Zwicker 2011.
+///
+/// Renders one sample per pixel per photon pass.
+pub struct PhotonMappingIntegrator {
+    photons: u32,
+    radius: f64,
+    alpha: f64,
+    roulette_bounces: Option<u32>,
+    map: RwLock<Option<Arc<PhotonMap>>>,
+}
+
+impl PhotonMappingIntegrator {
+    pub fn new() -> PhotonMappingIntegrator {
+        PhotonMappingIntegrator {
+            photons: 100_000,
+            radius: 0.1,
+            alpha: 2. / 3.,
+            roulette_bounces: Some(3),
+            map: RwLock::new(None),
+        }
+    }
+
+    /// Photons shot before every pass.
+    pub fn with_photons(mut self, photons: u32) -> PhotonMappingIntegrator {
+        self.photons = photons;
+        self
+    }
+
+    /// Gather radius of the first pass, in scene units.
+    pub fn with_radius(mut self, radius: f64) -> PhotonMappingIntegrator {
+        self.radius = radius;
+        self
+    }
+
+    /// How much of its photons a pass keeps when shrinking the radius, in
+    /// (0, 1). Smaller values shrink it faster.
+    pub fn with_alpha(mut self, alpha: f64) -> PhotonMappingIntegrator {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Bounces after which camera paths are ended by Russian roulette,
+    /// `None` follows every path to the ray depth.
+    pub fn with_russian_roulette(mut self, min_bounces: Option<u32>) -> PhotonMappingIntegrator {
+        self.roulette_bounces = min_bounces;
+        self
+    }
+
+    // Gather radius of the pass after `passes` others.
+    fn radius_after(&self, passes: u32) -> f64 {
+        let mut squared = self.radius * self.radius;
+        for pass in 1..=passes {
+            squared *= (pass as f64 + self.alpha) / (pass as f64 + 1.);
+        }
+        squared.sqrt()
+    }
+}
+
+impl Default for PhotonMappingIntegrator {
+    fn default() -> PhotonMappingIntegrator {
+        PhotonMappingIntegrator::new()
+    }
+}
+
+impl Integrator for PhotonMappingIntegrator {
+    fn name(&self) -> &'static str {
+        "photon"
+    }
+
+    fn pass_samples(&self) -> Option<u32> {
+        Some(1)
+    }
+
+    fn prepare(
+        &self,
+        scene: &HitList,
+        _camera: &(dyn Camera + Sync),
+        seed: u64,
+        samples: u32,
+        _depth: u32,
+    ) {
+        let emitters = Emitters::new(scene);
+        let chunks = self.photons.div_ceil(PHOTON_CHUNK);
+        let photons: Vec<Photon> = (0..chunks)
+            .into_par_iter()
+            .map(|chunk| {
+                let mut sampler = IndependentSampler::new();
+                sampler.set_seed(seed);
+                let first = chunk * PHOTON_CHUNK;
+                (first..(first + PHOTON_CHUNK).min(self.photons))
+                    .filter_map(|index| {
+                        // Rows past the image keep photons apart from camera samples.
+                        sampler.start_pixel_sample((samples, u32::MAX), index, self.photons);
+                        trace_photon(scene, &emitters, &mut sampler)
+                    })
+                    .collect::<Vec<Photon>>()
+            })
+            .flatten()
+            .collect();
+
+        let map = PhotonMap::new(photons, emitters, self.radius_after(samples), self.photons);
+        *self.map.write().unwrap() = Some(Arc::new(map));
+    }
+
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        let map = self.map.read().unwrap().clone();
+        let mut ray = *r;
+        let mut radiance = Radiance::zero();
+        let mut throughput = Vec3::new(1., 1., 1.);
+        let mut bounces = 0;
+        let mut previous: Option<(Point3, f64)> = None;
+        // Whether the path met a diffuse surface. Light reaching it over
+        // mirrors and glass only is what the photons bring.
+        let mut diffuse = false;
+
+        loop {
+            if bounces == depth {
+                stats::record(|stats| stats.terminated_by_depth += 1);
+                return (radiance, bounces);
+            }
+
+            let hit_rec = trace(scene, &ray);
+            let emitted = match &hit_rec {
+                Some(hit_rec) => hit_rec.material.emitted(hit_rec),
+                None => scene.environment.radiance(&ray.direction()),
+            };
+            // Left to the photons if they are shot from what the ray hit.
+            let caustic = diffuse
+                && previous.is_none()
+                && map
+                    .as_ref()
+                    .is_some_and(|map| map.emitters.shoots(scene, &ray, hit_rec.as_ref()));
+            if !is_black(&emitted) && !caustic {
+                let weight = match previous {
+                    Some((point, pdf)) => power_heuristic(
+                        pdf,
+                        light_pdf(scene, &point, &ray.direction(), hit_rec.as_ref()),
+                    ),
+                    None => 1.,
+                };
+                radiance.add(bounces, weight * (throughput * emitted));
+            }
+            let hit_rec = match hit_rec {
+                Some(hit_rec) => hit_rec,
+                None => return (radiance, bounces),
+            };
+            if bounces == 0 {
+                radiance.lobe = Some(hit_rec.material.lobe());
+            }
+            bounces += 1;
+            let material = hit_rec.material;
+
+            if !material.is_specular() && bounces < depth {
+                if let Some(light) = sample_light(scene, &hit_rec.hit_point, sampler) {
+                    let f = material.eval(&hit_rec, &light.direction);
+                    if light.pdf > 0.
+                        && !is_black(&f)
+                        && unoccluded(scene, &hit_rec.hit_point, &light.direction, light.distance)
+                    {
+                        let weight =
+                            power_heuristic(light.pdf, material.pdf(&hit_rec, &light.direction));
+                        radiance.add(
+                            bounces,
+                            (weight / light.pdf) * (throughput * f * light.radiance),
+                        );
+                    }
+                }
+                // Photons came over at least one more surface.
+                if let Some(map) = &map {
+                    radiance.add(bounces + 1, throughput * map.gather(&hit_rec));
+                }
+            }
+
+            ray = match material.scatter(&ray, &hit_rec, sampler) {
+                Some(scattered) => scattered,
+                None => return (radiance, bounces),
+            };
+            previous = if material.is_specular() {
+                None
+            } else {
+                diffuse = true;
+                Some((hit_rec.hit_point, material.pdf(&hit_rec, &ray.direction())))
+            };
+            throughput = throughput * material.attenuation();
+
+            if !russian_roulette(&mut throughput, bounces, self.roulette_bounces, sampler) {
+                return (radiance, bounces);
+            }
+        }
+    }
+}
+
+// Light come to rest on a diffuse surface after at least one specular
+// bounce. `direction` points back where it came from.
+#[derive(Copy, Clone)]
+struct Photon {
+    point: Point3,
+    normal: Vec3,
+    direction: Vec3,
+    power: Vec3,
+}
+
+// Photons of one pass sorted into cubic cells as large as the gather
+// radius, so a gather only looks at the 27 cells around it.
+struct PhotonMap {
+    emitters: Emitters,
+    radius: f64,
+    emitted: u32,
+    cells: HashMap<(i64, i64, i64), Vec<Photon>>,
+}
+
+impl PhotonMap {
+    fn new(photons: Vec<Photon>, emitters: Emitters, radius: f64, emitted: u32) -> PhotonMap {
+        let mut map = PhotonMap {
+            emitters,
+            radius,
+            emitted,
+            cells: HashMap::new(),
+        };
+        for photon in photons {
+            let cell = map.cell(&photon.point);
+            map.cells.entry(cell).or_default().push(photon);
+        }
+        map
+    }
+
+    fn cell(&self, p: &Point3) -> (i64, i64, i64) {
+        let index = |x: f64| (x / self.radius).floor() as i64;
+        (index(p.x_), index(p.y_), index(p.z_))
+    }
+
+    // Radiance the photons around `hit` scatter back along the ray that
+    // found it.
+    fn gather(&self, hit: &HitRecord) -> Vec3 {
+        let (x, y, z) = self.cell(&hit.hit_point);
+        let squared = self.radius * self.radius;
+        let mut sum = Vec3::zero();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let photons = match self.cells.get(&(x + dx, y + dy, z + dz)) {
+                        Some(photons) => photons,
+                        None => continue,
+                    };
+                    for photon in photons {
+                        let offset = photon.point - hit.hit_point;
+                        let cos = Vec3::dot(&hit.out_normal, &photon.direction);
+                        if Vec3::dot(&offset, &offset) > squared
+                            || cos <= 0.
+                            || Vec3::dot(&hit.out_normal, &photon.normal) <= 0.
+                        {
+                            continue;
+                        }
+                        let f = hit.material.eval(hit, &photon.direction);
+                        sum = sum + (1. / cos) * (f * photon.power);
+                    }
+                }
+            }
+        }
+        sum / (PI * squared * self.emitted as f64)
+    }
+}
+
+// Where photons come from: the scene's lights and, unless it is black,
+// the sky. Sky photons are only aimed at the spheres around the objects
+// that are mirrors or glass, as nothing else makes them caustic photons.
+struct Emitters {
+    casters: Vec<(Point3, f64)>,
+    // Sum of the squared radii of `casters`.
+    caster_area: f64,
+    // Sphere around the whole scene.
+    bounds: Option<(Point3, f64)>,
+}
+
+impl Emitters {
+    fn new(scene: &HitList) -> Emitters {
+        let casters: Vec<(Point3, f64)> = if scene.environment.is_black() {
+            Vec::new()
+        } else {
+            scene
+                .elements
+                .iter()
+                .filter_map(|element| caster(element.as_ref()))
+                .collect()
+        };
+        Emitters {
+            caster_area: casters.iter().map(|(_, radius)| radius * radius).sum(),
+            casters,
+            bounds: scene
+                .bounding_box()
+                .map(|bounds| (bounds.center(), bounds.half_size().length())),
+        }
+    }
+
+    fn count(&self, scene: &HitList) -> usize {
+        scene.lights.len() + self.sky() as usize
+    }
+
+    fn sky(&self) -> bool {
+        !self.casters.is_empty() && self.bounds.is_some()
+    }
+
+    // Whether photons travel back along `ray` from what it hit, `None` for
+    // the sky. Sky photons only pass through the casters.
+    fn shoots(&self, scene: &HitList, ray: &Ray, hit: Option<&HitRecord>) -> bool {
+        match hit {
+            Some(hit) => scene
+                .lights
+                .iter()
+                .any(|light| light.object_id == hit.object_id),
+            None => self.sky() && self.crossed(&ray.origin(), &ray.direction()) > 0,
+        }
+    }
+
+    // Number of casters the line through `through` along `direction` passes.
+    fn crossed(&self, through: &Point3, direction: &Vec3) -> usize {
+        let direction = Vec3::unit_vector(*direction);
+        self.casters
+            .iter()
+            .filter(|(center, radius)| {
+                let offset = *center - *through;
+                let along = Vec3::dot(&offset, &direction);
+                Vec3::dot(&offset, &offset) - along * along < radius * radius
+            })
+            .count()
+    }
+
+    // Starting ray and power of a photon, always taking two 1D and two 2D
+    // dimensions.
+    fn emit(&self, scene: &HitList, sampler: &mut dyn Sampler) -> Option<(Ray, Vec3)> {
+        let pick = sampler.get_1d();
+        let pick_caster = sampler.get_1d();
+        let u_position = sampler.get_2d();
+        let u_direction = sampler.get_2d();
+        let count = self.count(scene);
+        if count == 0 {
+            return None;
+        }
+
+        let index = ((pick * count as f64) as usize).min(count - 1);
+        if let Some(light) = scene.lights.get(index) {
+            let normal = warp::uniform_sphere(u_position);
+            let point = light.center + light.radius * normal;
+            let area = 4. * PI * light.radius * light.radius;
+            let direction = warp::cosine_hemisphere(&normal, u_direction);
+            // The cosine of the emission cancels against the direction's density.
+            let power = (PI * area * count as f64) * light.emission;
+            return Some((Ray::new(point, direction), power));
+        }
+
+        // A line from the sky through the disk facing it across one caster,
+        // its density counts every caster it passes through.
+        let (center, radius) = self.bounds?;
+        let from = warp::uniform_sphere(u_direction);
+        let direction = -from;
+        let mut pick = pick_caster * self.caster_area;
+        let (caster, caster_radius) = *self
+            .casters
+            .iter()
+            .find(|(_, radius)| {
+                pick -= radius * radius;
+                pick < 0.
+            })
+            .unwrap_or(&self.casters[self.casters.len() - 1]);
+        let (x, y) = warp::concentric_disk(u_position);
+        let (tangent, bitangent) = warp::basis(&direction);
+        let through = caster + caster_radius * (x * tangent + y * bitangent);
+        let crossed = self.crossed(&through, &direction).max(1);
+
+        let origin = through + ((through - center).length() + radius) * from;
+        let area = PI * self.caster_area / crossed as f64;
+        let power = (4. * PI * area * count as f64) * scene.environment.radiance(&from);
+        Some((Ray::new(origin, direction), power))
+    }
+}
+
+// Sphere around `element` if it shows a specular surface when looked at
+// from any side along the axes.
+fn caster(element: &(dyn Hitable + Send + Sync)) -> Option<(Point3, f64)> {
+    let bounds = element.bounding_box()?;
+    let (center, radius) = (bounds.center(), bounds.half_size().length());
+    if radius <= 0. || !radius.is_finite() {
+        return None;
+    }
+    let axes = [
+        Vec3::new(1., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        Vec3::new(0., 0., 1.),
+    ];
+    let specular = axes.iter().flat_map(|axis| [*axis, -*axis]).any(|axis| {
+        let probe = Ray::new(center + (2. * radius) * axis, -axis);
+        element
+            .hit(&probe, 0.001, f64::MAX)
+            .is_some_and(|hit| hit.material.is_specular())
+    });
+    if specular {
+        Some((center, radius))
+    } else {
+        None
+    }
+}
+
+// Follows a photon through mirrors and glass to the first diffuse surface,
+// where it is kept if it got there over at least one of them.
+fn trace_photon(scene: &HitList, emitters: &Emitters, sampler: &mut dyn Sampler) -> Option<Photon> {
+    let (mut ray, mut power) = emitters.emit(scene, sampler)?;
+    for bounces in 0..=MAX_SPECULAR_BOUNCES {
+        let hit = trace(scene, &ray)?;
+        let material = hit.material;
+        if !material.is_specular() {
+            if bounces == 0 {
+                return None;
+            }
+            return Some(Photon {
+                point: hit.hit_point,
+                normal: hit.out_normal,
+                direction: -Vec3::unit_vector(ray.direction()),
+                power,
+            });
+        }
+        let scattered = material.scatter(&ray, &hit, sampler)?;
+        power = material.light_scale(&hit, &scattered) * (power * material.attenuation());
+        ray = scattered;
+        if is_black(&power) {
+            return None;
+        }
+    }
+    None
+}
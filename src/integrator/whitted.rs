@@ -0,0 +1,116 @@
+use crate::integrator::{trace, unoccluded, Integrator};
+use crate::sampler::Sampler;
+use crate::stats;
+use crate::structs::film::Radiance;
+use crate::structs::hitable::{HitList, HitRecord};
+use crate::structs::ray::Ray;
+use crate::structs::vec3::Vec3;
+
+use std::f64::consts::PI;
+
+// Branches carrying less than this share of the light are not followed.
+const MIN_WEIGHT: f64 = 1e-3;
+
+/// Classic recursive ray tracer after Whitted: mirrors and glass split
+/// into their reflected and refracted rays, diffuse surfaces are lit by
+/// shadow rays to the center of every light plus the environment as
+/// ambient light. No noise besides the pixel and lens sampling, but no
+/// soft shadows or indirect light either.
+#[derive(Debug, Clone, Default)]
+pub struct WhittedIntegrator;
+
+impl WhittedIntegrator {
+    pub fn new() -> WhittedIntegrator {
+        WhittedIntegrator
+    }
+
+    // Light a diffuse surface gets from the lights and the environment.
+    fn direct(scene: &HitList, hit_rec: &HitRecord) -> Vec3 {
+        let material = hit_rec.material;
+        let mut radiance = material.attenuation() * scene.environment.radiance(&hit_rec.out_normal);
+
+        for light in &scene.lights {
+            let to_light = light.center - hit_rec.hit_point;
+            let distance = to_light.length();
+            if distance <= light.radius {
+                continue;
+            }
+            let f = material.eval(hit_rec, &to_light);
+            if f.x_ + f.y_ + f.z_ > 0.
+                && unoccluded(
+                    scene,
+                    &hit_rec.hit_point,
+                    &to_light,
+                    distance - light.radius,
+                )
+            {
+                // The sphere as a point, covering pi (r / d)^2 of solid angle.
+                let solid_angle = PI * (light.radius / distance).powi(2);
+                radiance = radiance + solid_angle * (f * light.emission);
+            }
+        }
+        radiance
+    }
+
+    // `scatters` counts the mirrors and glass the ray came over.
+    fn shade(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        depth: u32,
+        weight: f64,
+        scatters: u32,
+    ) -> (Radiance, u32) {
+        if depth == 0 {
+            stats::record(|stats| stats.terminated_by_depth += 1);
+            return (Radiance::zero(), 0);
+        }
+        let mut radiance = Radiance::zero();
+        let hit_rec = match trace(scene, r) {
+            Some(hit_rec) => hit_rec,
+            None => {
+                radiance.add(scatters, scene.environment.radiance(&r.direction()));
+                return (radiance, 0);
+            }
+        };
+
+        if scatters == 0 {
+            radiance.lobe = Some(hit_rec.material.lobe());
+        }
+        radiance.add(scatters, hit_rec.material.emitted(&hit_rec));
+        let lobes = hit_rec.material.specular_lobes(r, &hit_rec);
+        if lobes.is_empty() {
+            radiance.add(scatters + 1, WhittedIntegrator::direct(scene, &hit_rec));
+            return (radiance, 1);
+        }
+
+        let mut bounces = 0;
+        for (ray, share) in lobes {
+            let branch_weight = weight * share.x_.max(share.y_).max(share.z_);
+            if branch_weight < MIN_WEIGHT {
+                continue;
+            }
+            let (col, branch_bounces) =
+                self.shade(&ray, scene, depth - 1, branch_weight, scatters + 1);
+            radiance = radiance + share * col;
+            bounces = bounces.max(branch_bounces);
+        }
+        (radiance, bounces + 1)
+    }
+}
+
+impl Integrator for WhittedIntegrator {
+    fn name(&self) -> &'static str {
+        "whitted"
+    }
+
+    fn li(
+        &self,
+        r: &Ray,
+        scene: &HitList,
+        _sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> (Radiance, u32) {
+        self.shade(r, scene, depth, 1., 0)
+    }
+}
@@ -1,46 +1,224 @@
-use rusttracer::structs::vec3::Vec3;
-use rusttracer::structs::viewport::Viewport;
+use rusttracer::checkpoint::{self, CheckpointInfo};
+use rusttracer::denoise::Denoiser;
+use rusttracer::output::{self, aov};
+use rusttracer::settings::Settings;
+use rusttracer::stats::StatsFormat;
+use rusttracer::structs::film::Film;
+use rusttracer::structs::viewport::{Progress, Viewport};
 
-extern crate image;
-use image::{ImageBuffer, Rgb};
+use std::io::Write;
+use std::time::Duration;
 
-fn main() {
-    let aspect_ratio = 16. / 9.;
-    let img_width = 1200u32;
-    let img_height = (img_width as f64 / aspect_ratio) as u32;
-    let samples = 50;
-    let ray_depth = 50;
-
-    let lookfrom = Vec3::new(8., 3., 2.);
-    let lookat = Vec3::new(0., 0., 0.);
-    let vup = Vec3::new(0., 1., 0.);
-    let dist_to_focus = (lookfrom - lookat).length();
-    let aperture = 0.1;
-    let vertical_fov = 60.;
-
-    let viewport = Viewport::new(
-        lookfrom,
-        lookat,
-        vup,
-        vertical_fov,
-        aspect_ratio,
-        aperture,
-        dist_to_focus,
-    );
+const USAGE: &str = "usage: rusttracer-bin [--scene-file FILE] [-o|--output FILE] \
+[-f|--format png|exr|hdr|pfm] [--exposure EV] \
+[--tone-map clamp|reinhard|extended_reinhard|aces|agx|hable] [--aovs true|false] \
+[--denoise none|atrous] \
+[--filter box|tent|gaussian|mitchell|lanczos|blackman_harris] [--filter-radius PIXELS] \
+[--sampler independent|stratified|halton|sobol|blue_noise] [--seed N] \
+[--camera perspective|orthographic|fisheye_equidistant|fisheye_equisolid|equirectangular] \
+[--vfov DEGREES] [--ortho-height SIZE] [--fisheye-fov DEGREES] \
+[--aperture SIZE] [--aperture-shape circular|polygonal|mask] [--aperture-blades N] \
+[--aperture-rotation DEGREES] [--aperture-mask IMAGE] [--cat-eye STRENGTH] \
+[--chromatic-aberration LONGITUDINAL,LATERAL] \
+[--tile-size PIXELS] [--tile-order scanline|spiral|hilbert] \
+[--integrator path|path_mis|bdpt|photon|mlt|ao|direct|whitted] [--ao-distance DISTANCE] \
+[--photons N] [--photon-radius DISTANCE] \
+[--mlt-mutations N] [--mlt-bootstrap N] [--mlt-sigma SIZE] [--mlt-large-step CHANCE] \
+[--russian-roulette true|false] [--roulette-bounces N] \
+[--adaptive true|false] [--adaptive-threshold ERROR] [--max-samples N] \
+[--progressive true|false] [--pass-samples N] [--time-budget SECONDS] \
+[--snapshot-passes N] [--snapshot-interval SECONDS] \
+[--checkpoint FILE] [--checkpoint-interval SECONDS] [--resume true|false] \
+[--progress true|false] [--stats none|summary|json] \
+[--scene-seed N] [--scene-grid N] [--scene-materials DIFFUSE,METAL,GLASS] [--scene-radius MIN,MAX] \
+[--SETTING VALUE]...";
+
+fn checkpoint_info(settings: &Settings) -> CheckpointInfo {
+    CheckpointInfo {
+        settings_hash: settings.image_hash(),
+        sampler: settings.sampler.name().to_string(),
+        seed: settings.seed,
+    }
+}
+
+fn save_checkpoint(film: &Film, settings: &Settings) {
+    if let Some(path) = &settings.checkpoint {
+        if let Err(why) = checkpoint::save(film, &checkpoint_info(settings), path) {
+            println!("Unable to save {} : {}", path.display(), why);
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
 
-    let raw_pixels = viewport.render(
-        img_width,
-        img_height,
-        samples,
-        ray_depth,
-        rusttracer::benchmarking_scene(),
+fn draw_progress(progress: &Progress) {
+    const WIDTH: usize = 30;
+    let filled = (progress.fraction() * WIDTH as f64) as usize;
+    let eta = progress
+        .eta
+        .map_or_else(|| String::from("-"), format_duration);
+    let mut err = std::io::stderr();
+    let _ = write!(
+        err,
+        "\r[{}{}] {:5.1}% pass {} tile {}/{} elapsed {} eta {}\x1b[K",
+        "#".repeat(filled),
+        " ".repeat(WIDTH - filled),
+        100. * progress.fraction(),
+        progress.pass,
+        progress.tiles,
+        progress.tiles_per_pass,
+        format_duration(progress.elapsed),
+        eta
     );
+    let _ = err.flush();
+}
+
+// Clears the progress bar line so a message can go there.
+fn clear_progress(settings: &Settings) {
+    if settings.progress {
+        eprint!("\r\x1b[K");
+    }
+}
+
+fn main() {
+    let mut settings = Settings::default();
+    if let Err(why) = settings.apply_args(std::env::args().skip(1)) {
+        println!("{}\n{}", why, USAGE);
+        return;
+    }
+
+    let scene = match rusttracer::scene_by_name(&settings.scene, settings.sphere_field().as_ref()) {
+        Some(scene) => scene,
+        None => {
+            println!("Unknown scene {}", settings.scene);
+            return;
+        }
+    };
+
+    let mut viewport = Viewport::with_camera(settings.camera())
+        .with_filter(settings.filter)
+        .with_sampler(settings.sampler.box_clone())
+        .with_seed(settings.seed)
+        .with_tiles(settings.tile_size, settings.tile_order)
+        .with_integrator(settings.integrator());
+    if let Some(adaptive) = settings.adaptive {
+        viewport = viewport.with_adaptive_sampling(adaptive);
+    }
+    if settings.progress {
+        viewport = viewport.with_progress(draw_progress);
+    }
+    if settings.stats.is_some() {
+        viewport = viewport.with_stats();
+    }
+    // Ctrl-C or SIGTERM stops rendering, what is done so far is saved as usual.
+    let cancel = viewport.cancel_token();
+    if let Err(why) = ctrlc::set_handler(move || cancel.cancel()) {
+        println!("Unable to handle Ctrl-C and SIGTERM : {}", why);
+    }
+    // The denoiser is guided by the AOVs.
+    if settings.aovs || settings.denoiser != Denoiser::None {
+        viewport = viewport.with_aovs();
+    }
+
+    let mut film = match settings.progressive() {
+        Some(progressive) => {
+            let mut film = viewport.film(settings.width, settings.height());
+            if let Some(path) = settings
+                .checkpoint
+                .as_ref()
+                .filter(|p| settings.resume && p.exists())
+            {
+                match checkpoint::load(&mut film, path) {
+                    Ok(info) if info == checkpoint_info(&settings) => {
+                        println!("Resuming from {}", path.display())
+                    }
+                    Ok(_) => {
+                        println!(
+                            "Checkpoint {} was rendered with other settings",
+                            path.display()
+                        );
+                        return;
+                    }
+                    Err(why) => {
+                        println!("Unable to resume from {} : {}", path.display(), why);
+                        return;
+                    }
+                }
+            }
+
+            viewport.render_progressive(
+                &mut film,
+                &progressive,
+                settings.ray_depth,
+                &scene,
+                |film, passes| {
+                    clear_progress(&settings);
+                    match output::save(film, &settings.output, settings.format(), &settings.display)
+                    {
+                        Err(why) => {
+                            println!("Unable to save {} : {}", settings.output.display(), why)
+                        }
+                        Ok(_) => println!("Saved snapshot after {} passes", passes),
+                    }
+                },
+                |film, _| save_checkpoint(film, &settings),
+            );
+            save_checkpoint(&film, &settings);
+            film
+        }
+        None => viewport.render(
+            settings.width,
+            settings.height(),
+            settings.samples,
+            settings.ray_depth,
+            scene,
+        ),
+    };
+
+    clear_progress(&settings);
+    if viewport.cancel_token().is_cancelled() {
+        println!("Cancelled, saving the image rendered so far");
+    }
+    if let (Some(format), Some(stats)) = (settings.stats, viewport.stats()) {
+        match format {
+            StatsFormat::Summary => println!("{}", stats),
+            StatsFormat::Json => println!("{}", stats.to_json()),
+        }
+    }
 
-    let img_buf: ImageBuffer<Rgb<u8>, Vec<u8>> =
-        image::ImageBuffer::from_vec(img_width, img_height, raw_pixels).unwrap();
+    settings.denoiser.apply(&mut film);
+    if !settings.aovs {
+        film.discard_aovs();
+    }
 
-    match img_buf.save("result.png") {
-        Err(why) => println!("Unable to save result.png : {}", why),
+    let saved = output::save(
+        &film,
+        &settings.output,
+        settings.format(),
+        &settings.display,
+    )
+    .and_then(|_| match settings.adaptive {
+        // Heatmap of where the samples went.
+        Some(_) => output::save_pass(
+            &aov::sample_counts(&film),
+            film.width(),
+            film.height(),
+            &settings.output,
+            settings.format(),
+            &settings.display,
+        ),
+        None => Ok(()),
+    });
+    match saved {
+        Err(why) => println!("Unable to save {} : {}", settings.output.display(), why),
         Ok(_) => println!("Done!"),
     };
 }
@@ -0,0 +1,246 @@
+use crate::structs::film::{Film, Lobe};
+use crate::structs::vec3::Vec3;
+
+/// How the values of a pass are meant to be read.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PassKind {
+    /// Linear color, shown through the display transform.
+    Color,
+    /// Unit vectors like normals.
+    Direction,
+    /// Any other data, like positions, depth and motion vectors.
+    Data,
+    /// Integer ids stored as floats.
+    Id,
+    /// Counts, shown on a heat scale.
+    Count,
+}
+
+/// Single AOV image. Values are kept in `Vec3` even when a pass has fewer
+/// channels, `channels` names the used components.
+pub struct Pass {
+    pub name: String,
+    pub kind: PassKind,
+    pub channels: Vec<&'static str>,
+    pub values: Vec<Vec3>,
+}
+
+/// Averaged AOV passes of a film recorded with AOVs, empty otherwise.
+///
+/// The lighting passes hold the contribution of every lobe to the final
+/// image, they add up to it together with the background.
+pub fn passes(film: &Film) -> Vec<Pass> {
+    let aovs = match film.aovs() {
+        Some(aovs) => aovs,
+        None => return Vec::new(),
+    };
+
+    let hit_average = |f: &dyn Fn(&crate::structs::film::AovPixel) -> Vec3| -> Vec<Vec3> {
+        aovs.iter()
+            .map(|aov| {
+                if aov.hits == 0 {
+                    Vec3::zero()
+                } else {
+                    f(aov) / aov.hits as f64
+                }
+            })
+            .collect()
+    };
+    let pass = |name: &str, kind: PassKind, channels: Vec<&'static str>, values: Vec<Vec3>| Pass {
+        name: name.to_string(),
+        kind,
+        channels,
+        values,
+    };
+
+    let mut result = vec![
+        pass(
+            "albedo",
+            PassKind::Color,
+            vec!["R", "G", "B"],
+            hit_average(&|aov| aov.albedo),
+        ),
+        pass(
+            "normal",
+            PassKind::Direction,
+            vec!["X", "Y", "Z"],
+            aovs.iter()
+                .map(|aov| {
+                    if aov.hits == 0 {
+                        Vec3::zero()
+                    } else {
+                        Vec3::unit_vector(aov.normal)
+                    }
+                })
+                .collect(),
+        ),
+        pass(
+            "position",
+            PassKind::Data,
+            vec!["X", "Y", "Z"],
+            hit_average(&|aov| aov.position),
+        ),
+        pass(
+            "depth",
+            PassKind::Data,
+            vec!["Z"],
+            aovs.iter()
+                .map(|aov| {
+                    let depth = if aov.hits == 0 {
+                        f64::INFINITY
+                    } else {
+                        aov.depth / aov.hits as f64
+                    };
+                    Vec3::new(depth, depth, depth)
+                })
+                .collect(),
+        ),
+        pass(
+            "motion",
+            PassKind::Data,
+            vec!["X", "Y"],
+            hit_average(&|aov| Vec3::new(aov.motion.0, aov.motion.1, 0.)),
+        ),
+        pass(
+            "material_id",
+            PassKind::Id,
+            vec!["id"],
+            aovs.iter()
+                .map(|aov| Vec3::new(aov.material_id as f64, 0., 0.))
+                .collect(),
+        ),
+        pass(
+            "object_id",
+            PassKind::Id,
+            vec!["id"],
+            aovs.iter()
+                .map(|aov| Vec3::new(aov.object_id as f64, 0., 0.))
+                .collect(),
+        ),
+    ];
+
+    // Normalized like the radiance of the pixels, so the lighting passes
+    // add up to the image.
+    let light_scale = film.light_scale();
+    for (index, lobe) in Lobe::ALL.iter().enumerate() {
+        for (direct, suffix) in [(true, "direct"), (false, "indirect")].iter() {
+            let pick = |lobes: &[(Vec3, Vec3); 3]| {
+                if *direct {
+                    lobes[index].0
+                } else {
+                    lobes[index].1
+                }
+            };
+            let values = aovs
+                .iter()
+                .zip(film.pixels().iter())
+                .map(|(aov, pixel)| {
+                    let traced = light_scale * pick(&aov.light_lobes);
+                    if pixel.weight_sum.abs() < 1e-12 {
+                        traced
+                    } else {
+                        pick(&aov.lobes) / pixel.weight_sum + traced
+                    }
+                })
+                .collect();
+            result.push(pass(
+                &format!("{}_{}", lobe.name(), suffix),
+                PassKind::Color,
+                vec!["R", "G", "B"],
+                values,
+            ));
+        }
+    }
+
+    result
+}
+
+/// Samples taken in every pixel, shows where adaptive sampling spent its time.
+pub fn sample_counts(film: &Film) -> Pass {
+    Pass {
+        name: String::from("samples"),
+        kind: PassKind::Count,
+        channels: vec!["count"],
+        values: film
+            .pixels()
+            .iter()
+            .map(|pixel| {
+                let count = pixel.samples as f64;
+                Vec3::new(count, count, count)
+            })
+            .collect(),
+    }
+}
+
+// Black over blue, red and yellow to white for `t` in `[0, 1]`.
+fn heat(t: f64) -> Vec3 {
+    const STOPS: [(f64, f64, f64); 5] = [
+        (0., 0., 0.),
+        (0.1, 0.1, 0.8),
+        (0.9, 0.1, 0.2),
+        (1., 0.85, 0.1),
+        (1., 1., 1.),
+    ];
+    let scaled = num::clamp(t, 0., 1.) * (STOPS.len() - 1) as f64;
+    let index = (scaled as usize).min(STOPS.len() - 2);
+    let f = scaled - index as f64;
+    let (a, b) = (STOPS[index], STOPS[index + 1]);
+    Vec3::new(
+        a.0 + f * (b.0 - a.0),
+        a.1 + f * (b.1 - a.1),
+        a.2 + f * (b.2 - a.2),
+    )
+}
+
+/// Values of a non color pass squeezed into `[0, 1]` for 8 bit images.
+pub fn visualize(pass: &Pass) -> Vec<Vec3> {
+    match pass.kind {
+        PassKind::Color => pass.values.clone(),
+        PassKind::Direction => pass.values.iter().map(|n| 0.5 * (*n + 1.)).collect(),
+        PassKind::Data => {
+            let scale = pass
+                .values
+                .iter()
+                .flat_map(|v| vec![v.x_, v.y_, v.z_])
+                .filter(|c| c.is_finite())
+                .fold(0f64, |m, c| m.max(c.abs()));
+            let scale = if scale > 0. { scale } else { 1. };
+            pass.values
+                .iter()
+                .map(|v| {
+                    let map = |c: f64| {
+                        if c.is_finite() {
+                            0.5 + 0.5 * c / scale
+                        } else {
+                            1.
+                        }
+                    };
+                    Vec3::new(map(v.x_), map(v.y_), map(v.z_))
+                })
+                .collect()
+        }
+        PassKind::Count => {
+            let max = pass.values.iter().fold(0f64, |m, v| m.max(v.x_));
+            pass.values
+                .iter()
+                .map(|v| heat(if max > 0. { v.x_ / max } else { 0. }))
+                .collect()
+        }
+        PassKind::Id => pass
+            .values
+            .iter()
+            .map(|v| {
+                let id = v.x_ as u32;
+                if id == 0 {
+                    return Vec3::zero();
+                }
+                let hash = id.wrapping_mul(0x9e3779b1);
+                Vec3::new(
+                    (hash & 0xff) as f64 / 255.,
+                    ((hash >> 8) & 0xff) as f64 / 255.,
+                    ((hash >> 16) & 0xff) as f64 / 255.,
+                )
+            })
+            .collect(),
+    }
+}
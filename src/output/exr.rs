@@ -0,0 +1,105 @@
+use crate::structs::vec3::Vec3;
+
+use std::io::{self, Write};
+
+/// Single channel of an OpenEXR image, one value per pixel row by row.
+pub struct ExrChannel {
+    pub name: String,
+    pub values: Vec<f32>,
+}
+
+/// `R`, `G` and `B` channels of a layer, prefixed with `layer.` unless `layer` is empty.
+pub fn rgb_channels(layer: &str, pixels: &[Vec3]) -> Vec<ExrChannel> {
+    let prefix = if layer.is_empty() {
+        String::new()
+    } else {
+        format!("{}.", layer)
+    };
+    let channel = |name: &str, value: fn(&Vec3) -> f64| ExrChannel {
+        name: format!("{}{}", prefix, name),
+        values: pixels.iter().map(|p| value(p) as f32).collect(),
+    };
+
+    vec![
+        channel("R", |p| p.x_),
+        channel("G", |p| p.y_),
+        channel("B", |p| p.z_),
+    ]
+}
+
+/// Writes an uncompressed scanline OpenEXR file with 32 bit float channels.
+pub fn write_exr<W: Write>(
+    out: &mut W,
+    width: u32,
+    height: u32,
+    channels: &[ExrChannel],
+) -> io::Result<()> {
+    let mut channels: Vec<&ExrChannel> = channels.iter().collect();
+    // Readers expect channels sorted by name, both in the header and in the data.
+    channels.sort_by(|a, b| a.name.cmp(&b.name));
+    for channel in &channels {
+        assert_eq!(channel.values.len(), (width * height) as usize);
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01]);
+    header.extend_from_slice(&2u32.to_le_bytes());
+
+    let mut chlist = Vec::new();
+    for channel in &channels {
+        chlist.extend_from_slice(channel.name.as_bytes());
+        chlist.push(0);
+        // FLOAT pixels, linear flag and reserved bytes, x and y sampling.
+        chlist.extend_from_slice(&2i32.to_le_bytes());
+        chlist.extend_from_slice(&[0, 0, 0, 0]);
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+    }
+    chlist.push(0);
+
+    let mut window = Vec::new();
+    for v in &[0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend_from_slice(&v.to_le_bytes());
+    }
+
+    let mut attribute = |name: &str, kind: &str, value: &[u8]| {
+        header.extend_from_slice(name.as_bytes());
+        header.push(0);
+        header.extend_from_slice(kind.as_bytes());
+        header.push(0);
+        header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+        header.extend_from_slice(value);
+    };
+    attribute("channels", "chlist", &chlist);
+    attribute("compression", "compression", &[0]);
+    attribute("dataWindow", "box2i", &window);
+    attribute("displayWindow", "box2i", &window);
+    attribute("lineOrder", "lineOrder", &[0]);
+    attribute("pixelAspectRatio", "float", &1f32.to_le_bytes());
+    attribute("screenWindowCenter", "v2f", &[0; 8]);
+    attribute("screenWindowWidth", "float", &1f32.to_le_bytes());
+    header.push(0);
+
+    let line_size = 4 * width as u64 * channels.len() as u64;
+    let first_line = header.len() as u64 + 8 * height as u64;
+    for y in 0..height as u64 {
+        header.extend_from_slice(&(first_line + y * (8 + line_size)).to_le_bytes());
+    }
+    out.write_all(&header)?;
+
+    let mut line = Vec::with_capacity(8 + line_size as usize);
+    for y in 0..height {
+        line.clear();
+        line.extend_from_slice(&(y as i32).to_le_bytes());
+        line.extend_from_slice(&(line_size as i32).to_le_bytes());
+        for channel in &channels {
+            let row = &channel.values[(y * width) as usize..((y + 1) * width) as usize];
+            for value in row {
+                line.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
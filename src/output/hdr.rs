@@ -0,0 +1,62 @@
+use crate::structs::vec3::Vec3;
+
+use std::io::{self, Write};
+
+// Shared exponent encoding of a color.
+fn rgbe(col: &Vec3) -> [u8; 4] {
+    let v = col.x_.max(col.y_).max(col.z_);
+    if v < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    let exponent = v.log2().floor() as i32 + 1;
+    let scale = 256. / 2f64.powi(exponent);
+    [
+        (col.x_.max(0.) * scale).min(255.) as u8,
+        (col.y_.max(0.) * scale).min(255.) as u8,
+        (col.z_.max(0.) * scale).min(255.) as u8,
+        (exponent + 128).clamp(0, 255) as u8,
+    ]
+}
+
+/// Writes a Radiance RGBE image, `pixels` row by row from the top.
+pub fn write_hdr<W: Write>(
+    out: &mut W,
+    width: u32,
+    height: u32,
+    pixels: &[Vec3],
+) -> io::Result<()> {
+    assert_eq!(pixels.len(), (width * height) as usize);
+
+    write!(
+        out,
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        height, width
+    )?;
+
+    let run_length = (8..0x8000).contains(&width);
+    let mut line = Vec::with_capacity(4 * width as usize + 4);
+    for row in pixels.chunks(width as usize) {
+        let encoded: Vec<[u8; 4]> = row.iter().map(rgbe).collect();
+        line.clear();
+
+        if run_length {
+            // New style scanline with every component stored separately, as literal runs.
+            line.extend_from_slice(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+            for component in 0..4 {
+                for chunk in encoded.chunks(128) {
+                    line.push(chunk.len() as u8);
+                    line.extend(chunk.iter().map(|p| p[component]));
+                }
+            }
+        } else {
+            for pixel in &encoded {
+                line.extend_from_slice(pixel);
+            }
+        }
+
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+pub mod aov;
+pub mod exr;
+pub mod hdr;
+pub mod pfm;
+pub mod tonemap;
+
+use crate::output::aov::{Pass, PassKind};
+use crate::output::tonemap::DisplayTransform;
+use crate::structs::film::Film;
+use crate::structs::vec3::Vec3;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Exr,
+    Hdr,
+    Pfm,
+}
+
+impl ImageFormat {
+    pub fn from_name(name: &str) -> Option<ImageFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "exr" => Some(ImageFormat::Exr),
+            "hdr" | "rgbe" => Some(ImageFormat::Hdr),
+            "pfm" => Some(ImageFormat::Pfm),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(ImageFormat::from_name)
+    }
+}
+
+/// 8 bit sRGB pixels of the film.
+pub fn to_rgb8(film: &Film, display: &DisplayTransform) -> Vec<u8> {
+    let mut result = Vec::with_capacity(3 * film.pixels().len());
+    for col in film.colors() {
+        result.extend_from_slice(&display.to_srgb8(col));
+    }
+    result
+}
+
+/// Saves the film, float formats keep the linear radiance and ignore `display`.
+///
+/// AOVs recorded on the film go into layers of the same file for OpenEXR,
+/// other formats get one file per pass named `<stem>.<pass>.<extension>`.
+pub fn save(
+    film: &Film,
+    path: &Path,
+    format: ImageFormat,
+    display: &DisplayTransform,
+) -> io::Result<()> {
+    let (width, height) = (film.width(), film.height());
+    let colors = film.colors();
+    let passes = aov::passes(film);
+
+    if format == ImageFormat::Exr {
+        let mut channels = exr::rgb_channels("", &colors);
+        for pass in &passes {
+            for (component, name) in pass.channels.iter().enumerate() {
+                channels.push(exr::ExrChannel {
+                    name: format!("{}.{}", pass.name, name),
+                    values: pass
+                        .values
+                        .iter()
+                        .map(|v| [v.x_, v.y_, v.z_][component] as f32)
+                        .collect(),
+                });
+            }
+        }
+        let mut out = BufWriter::new(File::create(path)?);
+        exr::write_exr(&mut out, width, height, &channels)?;
+        return out.flush();
+    }
+
+    save_image(&colors, width, height, path, format, Some(display))?;
+    for pass in &passes {
+        save_pass(pass, width, height, path, format, display)?;
+    }
+
+    Ok(())
+}
+
+/// Saves a single pass next to the image at `path`, as `<stem>.<pass>.<extension>`.
+/// Passes that are not colors are visualized for PNG.
+pub fn save_pass(
+    pass: &Pass,
+    width: u32,
+    height: u32,
+    path: &Path,
+    format: ImageFormat,
+    display: &DisplayTransform,
+) -> io::Result<()> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("result");
+    let file_name = match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => format!("{}.{}.{}", stem, pass.name, extension),
+        None => format!("{}.{}", stem, pass.name),
+    };
+    let pass_path = path.with_file_name(file_name);
+
+    if format == ImageFormat::Png && pass.kind != PassKind::Color {
+        save_image(
+            &aov::visualize(pass),
+            width,
+            height,
+            &pass_path,
+            format,
+            None,
+        )
+    } else {
+        save_image(
+            &pass.values,
+            width,
+            height,
+            &pass_path,
+            format,
+            Some(display),
+        )
+    }
+}
+
+// Writes a single image, 8 bit images go through `display` or are stored
+// as they are when there is none.
+fn save_image(
+    pixels: &[Vec3],
+    width: u32,
+    height: u32,
+    path: &Path,
+    format: ImageFormat,
+    display: Option<&DisplayTransform>,
+) -> io::Result<()> {
+    if format == ImageFormat::Png {
+        let mut buffer = Vec::with_capacity(3 * pixels.len());
+        for col in pixels {
+            match display {
+                Some(display) => buffer.extend_from_slice(&display.to_srgb8(*col)),
+                None => buffer.extend(
+                    [col.x_, col.y_, col.z_]
+                        .iter()
+                        .map(|c| (255. * num::clamp(*c, 0., 1.) + 0.5) as u8),
+                ),
+            }
+        }
+        return image::save_buffer(path, &buffer, width, height, image::ColorType::Rgb8)
+            .map_err(io::Error::other);
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    match format {
+        ImageFormat::Exr => {
+            exr::write_exr(&mut out, width, height, &exr::rgb_channels("", pixels))?
+        }
+        ImageFormat::Hdr => hdr::write_hdr(&mut out, width, height, pixels)?,
+        ImageFormat::Pfm => pfm::write_pfm(&mut out, width, height, pixels)?,
+        ImageFormat::Png => unreachable!(),
+    }
+    out.flush()
+}
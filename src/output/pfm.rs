@@ -0,0 +1,29 @@
+use crate::structs::vec3::Vec3;
+
+use std::io::{self, Write};
+
+/// Writes a little endian color Portable Float Map, `pixels` row by row from the top.
+pub fn write_pfm<W: Write>(
+    out: &mut W,
+    width: u32,
+    height: u32,
+    pixels: &[Vec3],
+) -> io::Result<()> {
+    assert_eq!(pixels.len(), (width * height) as usize);
+
+    write!(out, "PF\n{} {}\n-1.0\n", width, height)?;
+
+    // PFM stores the bottom row first.
+    let mut line = Vec::with_capacity(12 * width as usize);
+    for row in pixels.chunks(width as usize).rev() {
+        line.clear();
+        for col in row {
+            line.extend_from_slice(&(col.x_ as f32).to_le_bytes());
+            line.extend_from_slice(&(col.y_ as f32).to_le_bytes());
+            line.extend_from_slice(&(col.z_ as f32).to_le_bytes());
+        }
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
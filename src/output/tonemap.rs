@@ -0,0 +1,145 @@
+use crate::structs::vec3::Vec3;
+
+/// Operator compressing scene radiance into the displayable `[0, 1]` range.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ToneMap {
+    Clamp,
+    Reinhard,
+    /// Reinhard reaching white at luminance `white` instead of infinity.
+    ExtendedReinhard {
+        white: f64,
+    },
+    /// Narkowicz fit of the ACES filmic curve.
+    Aces,
+    AgX,
+    /// Uncharted 2 filmic curve by John Hable.
+    Hable,
+}
+
+impl ToneMap {
+    pub fn from_name(name: &str) -> Option<ToneMap> {
+        match name.to_ascii_lowercase().as_str() {
+            "clamp" | "none" => Some(ToneMap::Clamp),
+            "reinhard" => Some(ToneMap::Reinhard),
+            "extended_reinhard" | "reinhard_extended" => {
+                Some(ToneMap::ExtendedReinhard { white: 4. })
+            }
+            "aces" => Some(ToneMap::Aces),
+            "agx" => Some(ToneMap::AgX),
+            "hable" | "uncharted2" => Some(ToneMap::Hable),
+            _ => None,
+        }
+    }
+
+    /// Maps linear radiance to linear display values.
+    pub fn apply(&self, col: Vec3) -> Vec3 {
+        let col = Vec3::new(col.x_.max(0.), col.y_.max(0.), col.z_.max(0.));
+        let mapped = match self {
+            ToneMap::Clamp => col,
+            ToneMap::Reinhard => scale_luminance(col, |l| l / (1. + l)),
+            ToneMap::ExtendedReinhard { white } => {
+                scale_luminance(col, |l| l * (1. + l / (white * white)) / (1. + l))
+            }
+            ToneMap::Aces => per_channel(col, |x| {
+                (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+            }),
+            ToneMap::AgX => agx(col),
+            ToneMap::Hable => {
+                let white = 11.2;
+                per_channel(col, |x| hable_curve(2. * x) / hable_curve(white))
+            }
+        };
+        per_channel(mapped, |x| num::clamp(x, 0., 1.))
+    }
+}
+
+fn per_channel<F: Fn(f64) -> f64>(col: Vec3, f: F) -> Vec3 {
+    Vec3::new(f(col.x_), f(col.y_), f(col.z_))
+}
+
+pub fn luminance(col: &Vec3) -> f64 {
+    0.2126 * col.x_ + 0.7152 * col.y_ + 0.0722 * col.z_
+}
+
+// Tone maps the luminance only so hues are kept.
+fn scale_luminance<F: Fn(f64) -> f64>(col: Vec3, f: F) -> Vec3 {
+    let l = luminance(&col);
+    if l <= 0. {
+        col
+    } else {
+        f(l) / l * col
+    }
+}
+
+fn hable_curve(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.5, 0.1, 0.2, 0.02, 0.3);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+fn mat3_mul(m: &[[f64; 3]; 3], v: Vec3) -> Vec3 {
+    Vec3::new(
+        m[0][0] * v.x_ + m[0][1] * v.y_ + m[0][2] * v.z_,
+        m[1][0] * v.x_ + m[1][1] * v.y_ + m[1][2] * v.z_,
+        m[2][0] * v.x_ + m[2][1] * v.y_ + m[2][2] * v.z_,
+    )
+}
+
+// Minimal AgX: inset into a wider gamut, log encode, apply the default
+// contrast sigmoid, outset and undo the 2.2 display gamma it bakes in.
+fn agx(col: Vec3) -> Vec3 {
+    const INSET: [[f64; 3]; 3] = [
+        [0.842479062253094, 0.0784335999999992, 0.0792237451477643],
+        [0.0423282422610123, 0.878468636469772, 0.0791661274605434],
+        [0.0423756549057051, 0.0784336, 0.879142973793104],
+    ];
+    const OUTSET: [[f64; 3]; 3] = [
+        [1.19687900512017, -0.0980208811401368, -0.0990297440797205],
+        [-0.0528968517574562, 1.15190312990417, -0.0989611768448433],
+        [-0.0529716355144438, -0.0980434501171241, 1.15107367264116],
+    ];
+    let (min_ev, max_ev) = (-12.47393, 4.026069);
+
+    let encoded = per_channel(mat3_mul(&INSET, col), |x| {
+        let ev = num::clamp(x.max(1e-10).log2(), min_ev, max_ev);
+        let x = (ev - min_ev) / (max_ev - min_ev);
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    });
+    per_channel(mat3_mul(&OUTSET, encoded), |x| x.max(0.).powf(2.2))
+}
+
+/// sRGB transfer function for a linear value in `[0, 1]`.
+pub fn srgb_encode(linear: f64) -> f64 {
+    if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Conversion from linear radiance to 8 bit sRGB.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DisplayTransform {
+    /// Exposure adjustment in stops, every stop doubles the brightness.
+    pub exposure: f64,
+    pub tone_map: ToneMap,
+}
+
+impl Default for DisplayTransform {
+    fn default() -> DisplayTransform {
+        DisplayTransform {
+            exposure: 0.,
+            tone_map: ToneMap::Clamp,
+        }
+    }
+}
+
+impl DisplayTransform {
+    pub fn to_srgb8(&self, col: Vec3) -> [u8; 3] {
+        let mapped = self.tone_map.apply(2f64.powf(self.exposure) * col);
+        let encode = |x: f64| (255. * srgb_encode(x) + 0.5) as u8;
+        [encode(mapped.x_), encode(mapped.y_), encode(mapped.z_)]
+    }
+}
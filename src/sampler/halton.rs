@@ -0,0 +1,101 @@
+use crate::sampler::{
+    hash, mix_bits, permutation_element, IndependentSampler, Sampler, ONE_MINUS_EPSILON,
+};
+
+// Bases of the first dimensions, later ones are drawn independently.
+const PRIMES: [u64; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+/// Halton sequence, dimension `d` is the radical inverse of the sample
+/// index in the `d`th prime. Every pixel gets its own Owen scrambling of
+/// the digits so neighbouring pixels are not correlated.
+#[derive(Clone)]
+pub struct HaltonSampler {
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+    fallback: IndependentSampler,
+}
+
+impl HaltonSampler {
+    pub fn new() -> HaltonSampler {
+        HaltonSampler {
+            seed: 0,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+            fallback: IndependentSampler::new(),
+        }
+    }
+}
+
+impl Default for HaltonSampler {
+    fn default() -> HaltonSampler {
+        HaltonSampler::new()
+    }
+}
+
+// Radical inverse of `a` with every digit permuted depending on the
+// digits below it.
+fn owen_scrambled_radical_inverse(base: u64, mut a: u64, seed: u64) -> f64 {
+    let inv_base = 1. / base as f64;
+    let mut inv_base_m = 1.;
+    let mut reversed = 0u64;
+    while 1. - inv_base_m < 1. {
+        let next = a / base;
+        let digit = (a - next * base) as u32;
+        let digit_seed = mix_bits(seed ^ reversed) as u32;
+        let digit = permutation_element(digit, base as u32, digit_seed);
+        reversed = reversed * base + digit as u64;
+        inv_base_m *= inv_base;
+        a = next;
+    }
+    (inv_base_m * reversed as f64).min(ONE_MINUS_EPSILON)
+}
+
+impl Sampler for HaltonSampler {
+    fn name(&self) -> &'static str {
+        "halton"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.fallback.set_seed(seed);
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32, count: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+        self.fallback.start_pixel_sample(pixel, index, count);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.dimension as usize;
+        self.dimension += 1;
+        if dimension >= PRIMES.len() {
+            return self.fallback.get_1d();
+        }
+
+        let seed = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            dimension as u64,
+        ]);
+        owen_scrambled_radical_inverse(PRIMES[dimension], self.index as u64, seed)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.get_1d(), self.get_1d())
+    }
+
+    fn box_clone(&self) -> Box<dyn Sampler + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
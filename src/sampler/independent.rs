@@ -0,0 +1,64 @@
+use crate::sampler::{hash, to_unit, Sampler};
+
+/// Uncorrelated uniform numbers, every dimension of every sample is
+/// drawn on its own.
+#[derive(Clone)]
+pub struct IndependentSampler {
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl IndependentSampler {
+    pub fn new() -> IndependentSampler {
+        IndependentSampler {
+            seed: 0,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+}
+
+impl Default for IndependentSampler {
+    fn default() -> IndependentSampler {
+        IndependentSampler::new()
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn name(&self) -> &'static str {
+        "independent"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32, _count: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let bits = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.index as u64,
+            self.dimension as u64,
+        ]);
+        self.dimension += 1;
+        to_unit(bits)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.get_1d(), self.get_1d())
+    }
+
+    fn box_clone(&self) -> Box<dyn Sampler + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
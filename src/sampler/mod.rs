@@ -0,0 +1,111 @@
+mod halton;
+mod independent;
+mod sobol;
+mod stratified;
+pub mod warp;
+
+pub use halton::HaltonSampler;
+pub use independent::IndependentSampler;
+pub use sobol::{BlueNoiseSampler, SobolSampler};
+pub use stratified::StratifiedSampler;
+
+/// Source of the sample points a renderer turns into camera rays and
+/// scattering directions, one point in `[0, 1)^n` per pixel sample.
+///
+/// Every `get_1d` or `get_2d` call hands out the next dimensions of the
+/// current point, so they have to be requested in the same order for
+/// every sample: pixel position first, then the lens, then every bounce.
+pub trait Sampler {
+    fn name(&self) -> &'static str;
+
+    /// Picks another set of points. The points of a sample only depend on
+    /// the seed, the pixel and the sample index, so renders are repeatable
+    /// however the work is spread over threads.
+    fn set_seed(&mut self, seed: u64);
+
+    /// Starts sample `index` of pixel `(x, y)`, out of the `count` samples
+    /// the pixel is planned to get in total.
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32, count: u32);
+
+    fn get_1d(&mut self) -> f64;
+
+    fn get_2d(&mut self) -> (f64, f64);
+
+    /// Copy for another thread, every thread needs its own.
+    fn box_clone(&self) -> Box<dyn Sampler + Send + Sync>;
+}
+
+pub fn by_name(name: &str) -> Option<Box<dyn Sampler + Send + Sync>> {
+    let sampler: Box<dyn Sampler + Send + Sync> = match name.to_ascii_lowercase().as_str() {
+        "independent" | "random" => Box::new(IndependentSampler::new()),
+        "stratified" | "jittered" => Box::new(StratifiedSampler::new()),
+        "halton" => Box::new(HaltonSampler::new()),
+        "sobol" => Box::new(SobolSampler::new()),
+        "blue_noise" | "blue-noise" | "zsobol" => Box::new(BlueNoiseSampler::new()),
+        _ => return None,
+    };
+    Some(sampler)
+}
+
+// Largest f64 below 1.
+const ONE_MINUS_EPSILON: f64 = 1. - f64::EPSILON / 2.;
+
+// Finalizer of MurmurHash3, spreads every input bit over the output.
+fn mix_bits(mut v: u64) -> u64 {
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5d329728ea185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81dadef4bc2dd44d);
+    v ^= v >> 33;
+    v
+}
+
+fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e3779b97f4a7c15, |h, v| {
+        mix_bits(h ^ v.wrapping_add(0x9e3779b97f4a7c15))
+    })
+}
+
+// Uniform number in `[0, 1)` from the top 53 bits.
+fn to_unit(bits: u64) -> f64 {
+    (bits >> 11) as f64 * (1. / (1u64 << 53) as f64)
+}
+
+fn to_unit_32(bits: u32) -> f64 {
+    (bits as f64 * (1. / 4294967296.)).min(ONE_MINUS_EPSILON)
+}
+
+// Element `i` of a random permutation of `0..n` chosen by `seed`,
+// without building the permutation, after Kensler 2013.
+fn permutation_element(mut i: u32, n: u32, seed: u32) -> u32 {
+    let mut w = n - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < n {
+            break;
+        }
+    }
+    ((i as u64 + seed as u64) % n as u64) as u32
+}
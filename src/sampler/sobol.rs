@@ -0,0 +1,252 @@
+use crate::sampler::{hash, mix_bits, to_unit_32, Sampler};
+
+// First two dimensions of the Sobol sequence as 32 bit fractions, these
+// form a (0, 2) sequence in base 2.
+fn sobol_2d(index: u32) -> (u32, u32) {
+    let mut v = 1u32 << 31;
+    let mut y = 0;
+    let mut i = index;
+    while i != 0 {
+        if i & 1 != 0 {
+            y ^= v;
+        }
+        i >>= 1;
+        v ^= v >> 1;
+    }
+    (index.reverse_bits(), y)
+}
+
+// Owen scrambling of a 32 bit fraction by a hash that only lets every bit
+// depend on the bits above it, after Laine and Karras 2011.
+fn owen_scramble(v: u32, seed: u32) -> u32 {
+    let mut v = v.reverse_bits();
+    v ^= v.wrapping_mul(0x3d20adea);
+    v = v.wrapping_add(seed);
+    v = v.wrapping_mul((seed >> 16) | 1);
+    v ^= v.wrapping_mul(0x05526c56);
+    v ^= v.wrapping_mul(0x53a22864);
+    v.reverse_bits()
+}
+
+// Scrambled 2D Sobol point, the two halves of `seed` scramble one axis each.
+fn scrambled_2d(index: u32, seed: u64) -> (f64, f64) {
+    let (x, y) = sobol_2d(index);
+    (
+        to_unit_32(owen_scramble(x, seed as u32)),
+        to_unit_32(owen_scramble(y, (seed >> 32) as u32)),
+    )
+}
+
+/// Owen scrambled Sobol points. Every pair of dimensions is a separately
+/// scrambled copy of the first two Sobol dimensions with the sample order
+/// shuffled, which keeps the 2D stratification a path needs per bounce.
+/// The shuffle keeps every power of two prefix of the samples stratified,
+/// so sample counts that are powers of two work best.
+#[derive(Clone)]
+pub struct SobolSampler {
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new() -> SobolSampler {
+        SobolSampler {
+            seed: 0,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next(&mut self) -> (f64, f64) {
+        let seed = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+        ]);
+        // Nested uniform scrambling of the index, after Burley 2020.
+        let index = owen_scramble(self.index, mix_bits(seed) as u32);
+        scrambled_2d(index, seed)
+    }
+}
+
+impl Default for SobolSampler {
+    fn default() -> SobolSampler {
+        SobolSampler::new()
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn name(&self) -> &'static str {
+        "sobol"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32, _count: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let u = self.next().0;
+        self.dimension += 1;
+        u
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let u = self.next();
+        self.dimension += 2;
+        u
+    }
+
+    fn box_clone(&self) -> Box<dyn Sampler + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+// Pixel coordinates are taken modulo this many pixels.
+const BLUE_NOISE_RESOLUTION_DIGITS: u32 = 12;
+
+// All orderings of a base 4 digit.
+const PERMUTATIONS: [[u64; 4]; 24] = [
+    [0, 1, 2, 3],
+    [0, 1, 3, 2],
+    [0, 2, 1, 3],
+    [0, 2, 3, 1],
+    [0, 3, 2, 1],
+    [0, 3, 1, 2],
+    [1, 0, 2, 3],
+    [1, 0, 3, 2],
+    [1, 2, 0, 3],
+    [1, 2, 3, 0],
+    [1, 3, 2, 0],
+    [1, 3, 0, 2],
+    [2, 1, 0, 3],
+    [2, 1, 3, 0],
+    [2, 0, 1, 3],
+    [2, 0, 3, 1],
+    [2, 3, 0, 1],
+    [2, 3, 1, 0],
+    [3, 1, 2, 0],
+    [3, 1, 0, 2],
+    [3, 2, 1, 0],
+    [3, 2, 0, 1],
+    [3, 0, 2, 1],
+    [3, 0, 1, 2],
+];
+
+// Interleaves the bits of `x` and `y`.
+fn morton_2d(x: u32, y: u32) -> u64 {
+    let spread = |v: u32| {
+        let mut v = v as u64;
+        v = (v | (v << 16)) & 0x0000ffff0000ffff;
+        v = (v | (v << 8)) & 0x00ff00ff00ff00ff;
+        v = (v | (v << 4)) & 0x0f0f0f0f0f0f0f0f;
+        v = (v | (v << 2)) & 0x3333333333333333;
+        v = (v | (v << 1)) & 0x5555555555555555;
+        v
+    };
+    spread(x) | (spread(y) << 1)
+}
+
+/// Sobol points spread over the whole image in Morton order with shuffled
+/// base 4 digits, after Ahmed and Wonka 2020. Neighbouring pixels get
+/// well distributed parts of one sequence, so the error left at low
+/// sample counts looks like blue noise instead of white noise.
+#[derive(Clone)]
+pub struct BlueNoiseSampler {
+    seed: u64,
+    morton_index: u64,
+    log2_samples: u32,
+    dimension: u32,
+}
+
+impl BlueNoiseSampler {
+    pub fn new() -> BlueNoiseSampler {
+        BlueNoiseSampler {
+            seed: 0,
+            morton_index: 0,
+            log2_samples: 0,
+            dimension: 0,
+        }
+    }
+
+    // Index into the image wide sequence, the Morton index with every base 4
+    // digit permuted depending on the digits above it and the dimension.
+    fn sample_index(&self) -> u32 {
+        let odd = self.log2_samples & 1;
+        let digits = BLUE_NOISE_RESOLUTION_DIGITS + self.log2_samples.div_ceil(2);
+        let dimension_bits = 0x55555555u64.wrapping_mul(self.dimension as u64) ^ self.seed;
+
+        let mut index = 0u64;
+        for i in (odd..digits).rev() {
+            let shift = 2 * i - odd;
+            let digit = (self.morton_index >> shift) & 3;
+            let higher = self.morton_index >> (shift + 2);
+            let permutation = ((mix_bits(higher ^ dimension_bits) >> 24) % 24) as usize;
+            index |= PERMUTATIONS[permutation][digit as usize] << shift;
+        }
+        if odd == 1 {
+            let digit = self.morton_index & 1;
+            index |= digit ^ (mix_bits((self.morton_index >> 1) ^ dimension_bits) & 1);
+        }
+        // The 32 bit Sobol points only see the low bits.
+        index as u32
+    }
+}
+
+impl Default for BlueNoiseSampler {
+    fn default() -> BlueNoiseSampler {
+        BlueNoiseSampler::new()
+    }
+}
+
+impl Sampler for BlueNoiseSampler {
+    fn name(&self) -> &'static str {
+        "blue_noise"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32, count: u32) {
+        let mask = (1 << BLUE_NOISE_RESOLUTION_DIGITS) - 1;
+        // Samples past `count` still need room in the index.
+        let samples = count.max(index + 1).next_power_of_two();
+        self.log2_samples = samples.trailing_zeros();
+        self.morton_index =
+            (morton_2d(pixel.0 & mask, pixel.1 & mask) << self.log2_samples) | index as u64;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let u = scrambled_2d(
+            self.sample_index(),
+            hash(&[self.seed, self.dimension as u64]),
+        )
+        .0;
+        self.dimension += 1;
+        u
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let u = scrambled_2d(
+            self.sample_index(),
+            hash(&[self.seed, self.dimension as u64]),
+        );
+        self.dimension += 2;
+        u
+    }
+
+    fn box_clone(&self) -> Box<dyn Sampler + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::sampler::{hash, permutation_element, to_unit, Sampler};
+
+/// Jittered sampling, every dimension is split into as many strata as the
+/// pixel gets samples and each sample falls into a different one. The
+/// strata are shuffled independently per pixel and dimension.
+#[derive(Clone)]
+pub struct StratifiedSampler {
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    count: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new() -> StratifiedSampler {
+        StratifiedSampler {
+            seed: 0,
+            pixel: (0, 0),
+            index: 0,
+            count: 1,
+            dimension: 0,
+        }
+    }
+
+    // Stratum of the current sample among `strata` and the jitter inside it.
+    fn stratum(&mut self, strata: u32) -> (u32, f64) {
+        let (x, y) = (self.pixel.0 as u64, self.pixel.1 as u64);
+        let dimension = self.dimension as u64;
+        let permutation = hash(&[self.seed, x, y, dimension]) as u32;
+        let jitter = to_unit(hash(&[self.seed, x, y, dimension, self.index as u64]));
+        self.dimension += 1;
+
+        (
+            permutation_element(self.index % strata, strata, permutation),
+            jitter,
+        )
+    }
+}
+
+impl Default for StratifiedSampler {
+    fn default() -> StratifiedSampler {
+        StratifiedSampler::new()
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn name(&self) -> &'static str {
+        "stratified"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32, count: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.count = count.max(1);
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let (stratum, jitter) = self.stratum(self.count);
+        (stratum as f64 + jitter) / self.count as f64
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        // Grid with at least `count` cells, as square as possible.
+        let nx = (self.count as f64).sqrt().ceil() as u32;
+        let ny = self.count.div_ceil(nx);
+        let (stratum, jitter_x) = self.stratum(nx * ny);
+        let jitter_y = to_unit(hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+            self.index as u64,
+        ]));
+        self.dimension += 1;
+
+        (
+            ((stratum % nx) as f64 + jitter_x) / nx as f64,
+            ((stratum / nx) as f64 + jitter_y) / ny as f64,
+        )
+    }
+
+    fn box_clone(&self) -> Box<dyn Sampler + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
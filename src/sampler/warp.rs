@@ -0,0 +1,64 @@
+//! Mappings from uniform sample points onto the shapes the renderer draws
+//! directions and positions from. They are continuous so a well spread set
+//! of points stays well spread.
+
+use crate::structs::vec3::Vec3;
+
+use std::f64::consts::PI;
+
+/// Point in the unit disk, concentric mapping by Shirley and Chiu.
+pub fn concentric_disk((u1, u2): (f64, f64)) -> (f64, f64) {
+    let (a, b) = (2. * u1 - 1., 2. * u2 - 1.);
+    if a == 0. && b == 0. {
+        return (0., 0.);
+    }
+    let (radius, angle) = if a.abs() > b.abs() {
+        (a, PI / 4. * (b / a))
+    } else {
+        (b, PI / 2. - PI / 4. * (a / b))
+    };
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+/// Direction on the unit sphere.
+pub fn uniform_sphere((u1, u2): (f64, f64)) -> Vec3 {
+    let z = 1. - 2. * u1;
+    let r = (1. - z * z).max(0.).sqrt();
+    let phi = 2. * PI * u2;
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Point inside the unit sphere.
+pub fn unit_ball(u: f64, u2: (f64, f64)) -> Vec3 {
+    u.cbrt() * uniform_sphere(u2)
+}
+
+/// Two unit vectors completing `n` to an orthonormal basis, after Duff et al. 2017.
+pub fn basis(n: &Vec3) -> (Vec3, Vec3) {
+    let sign = 1f64.copysign(n.z_);
+    let a = -1. / (sign + n.z_);
+    let b = n.x_ * n.y_ * a;
+    (
+        Vec3::new(1. + sign * n.x_ * n.x_ * a, sign * b, -sign * n.x_),
+        Vec3::new(b, sign + n.y_ * n.y_ * a, -n.y_),
+    )
+}
+
+/// Direction around the unit vector `normal` with density proportional to
+/// the cosine to it.
+pub fn cosine_hemisphere(normal: &Vec3, u: (f64, f64)) -> Vec3 {
+    let (x, y) = concentric_disk(u);
+    let z = (1. - x * x - y * y).max(0.).sqrt();
+    let (tangent, bitangent) = basis(normal);
+    x * tangent + y * bitangent + z * normal
+}
+
+/// Direction inside the cone around the unit vector `axis` whose half angle
+/// has the cosine `cos_max`, uniform over its solid angle.
+pub fn uniform_cone(axis: &Vec3, cos_max: f64, (u1, u2): (f64, f64)) -> Vec3 {
+    let cos_theta = 1. - u1 * (1. - cos_max);
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    let phi = 2. * PI * u2;
+    let (tangent, bitangent) = basis(axis);
+    (sin_theta * phi.cos()) * tangent + (sin_theta * phi.sin()) * bitangent + cos_theta * axis
+}
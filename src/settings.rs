@@ -0,0 +1,720 @@
+use crate::denoise::Denoiser;
+use crate::integrator::{self, Integrator, IntegratorOptions};
+use crate::output::tonemap::{DisplayTransform, ToneMap};
+use crate::output::ImageFormat;
+use crate::sampler::{self, Sampler, SobolSampler};
+use crate::stats::StatsFormat;
+use crate::structs::aperture::{Aperture, ApertureMask};
+use crate::structs::camera::{
+    Camera, CameraModel, EquirectangularCamera, FisheyeCamera, OrthographicCamera,
+    PerspectiveCamera,
+};
+use crate::structs::filter::Filter;
+use crate::structs::tiles::TileOrder;
+use crate::structs::vec3::{Point3, Vec3};
+use crate::structs::viewport::{AdaptiveSampling, Progressive};
+use crate::SphereField;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// A million spheres, more takes ages to build and is surely a typo.
+const MAX_SCENE_GRID: u32 = 1000;
+
+/// Everything needed to render and save an image.
+///
+/// Settings are set by name, either from a scene file with one
+/// `key = value` pair per line (`#` starts a comment) or from command
+/// line flags `--key value`. Vectors are written as three numbers.
+pub struct Settings {
+    pub scene: String,
+    /// Parameters of the random scenes, unset ones keep the scene's usual value.
+    pub scene_seed: u64,
+    pub scene_grid: Option<u32>,
+    pub scene_materials: Option<[f64; 3]>,
+    pub scene_radius: Option<(f64, f64)>,
+    pub width: u32,
+    pub aspect_ratio: f64,
+    pub samples: u32,
+    pub ray_depth: u32,
+    /// Light transport algorithm, one of the `integrator::by_name` names.
+    pub integrator: String,
+    /// Bounces after which Russian roulette may end paths, `None` turns it off.
+    pub roulette_bounces: Option<u32>,
+    /// How far ambient occlusion looks for occluders.
+    pub ao_distance: f64,
+    /// Photons shot per pass by photon mapping.
+    pub photons: u32,
+    /// Gather radius photon mapping starts with.
+    pub photon_radius: f64,
+    /// Markov chain steps per camera sample of Metropolis light transport.
+    pub mlt_mutations: u32,
+    /// Bootstrap paths Metropolis light transport traces per pass.
+    pub mlt_bootstrap: u32,
+    /// Standard deviation of the small Metropolis steps.
+    pub mlt_sigma: f64,
+    /// Chance of a large Metropolis step.
+    pub mlt_large_step: f64,
+    pub camera: CameraModel,
+    pub lookfrom: Point3,
+    pub lookat: Point3,
+    pub vup: Vec3,
+    /// Vertical field of view of the perspective camera, in degrees.
+    pub vfov: f64,
+    /// Height of the film of the orthographic camera, in world units.
+    pub ortho_height: f64,
+    /// Angle the image circle of the fisheye cameras covers, in degrees.
+    pub fisheye_fov: f64,
+    pub aperture: f64,
+    /// Shape of the lens opening of the perspective camera.
+    pub aperture_shape: Aperture,
+    /// Image the mask aperture was loaded from.
+    pub aperture_mask: Option<PathBuf>,
+    /// Shift of the lens barrel clipping the aperture at the film edge.
+    pub cat_eye: f64,
+    /// Longitudinal and lateral chromatic aberration.
+    pub chromatic_aberration: (f64, f64),
+    /// Distance from `lookfrom` to `lookat` when not set.
+    pub focus_dist: Option<f64>,
+    pub output: PathBuf,
+    /// Taken from the `output` extension when not set.
+    pub format: Option<ImageFormat>,
+    pub display: DisplayTransform,
+    /// Record and save albedo, normal, depth and other passes.
+    pub aovs: bool,
+    pub denoiser: Denoiser,
+    pub filter: Filter,
+    pub sampler: Box<dyn Sampler + Send + Sync>,
+    /// Renders with the same seed and settings are bit identical.
+    pub seed: u64,
+    /// Edge length of the square tiles the image is rendered in.
+    pub tile_size: u32,
+    pub tile_order: TileOrder,
+    /// Keep sampling noisy pixels after the first `samples`.
+    pub adaptive: Option<AdaptiveSampling>,
+    /// Render pass by pass, saving snapshots of the image on the way.
+    /// Implied by the time budget, snapshot and checkpoint settings.
+    pub progressive: bool,
+    pub pass_samples: u32,
+    /// Seconds.
+    pub time_budget: Option<f64>,
+    pub snapshot_passes: Option<u32>,
+    /// Seconds.
+    pub snapshot_interval: Option<f64>,
+    /// File the render state is saved to after every pass and at the end.
+    pub checkpoint: Option<PathBuf>,
+    /// Seconds between checkpoints, instead of one every pass.
+    pub checkpoint_interval: Option<f64>,
+    /// Continue from the checkpoint file when there is one.
+    pub resume: bool,
+    /// Show a progress bar while rendering.
+    pub progress: bool,
+    /// Count rays and intersection tests and print them at the end.
+    pub stats: Option<StatsFormat>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            scene: String::from("benchmarking"),
+            scene_seed: 0,
+            scene_grid: None,
+            scene_materials: None,
+            scene_radius: None,
+            width: 1200,
+            aspect_ratio: 16. / 9.,
+            samples: 50,
+            ray_depth: 50,
+            integrator: String::from("path"),
+            roulette_bounces: Some(3),
+            ao_distance: 1.,
+            photons: 100_000,
+            photon_radius: 0.1,
+            mlt_mutations: 100,
+            mlt_bootstrap: 100_000,
+            mlt_sigma: 0.01,
+            mlt_large_step: 0.3,
+            camera: CameraModel::Perspective,
+            lookfrom: Point3::new(8., 3., 2.),
+            lookat: Point3::new(0., 0., 0.),
+            vup: Vec3::new(0., 1., 0.),
+            vfov: 60.,
+            ortho_height: 10.,
+            fisheye_fov: 180.,
+            aperture: 0.1,
+            aperture_shape: Aperture::Circular,
+            aperture_mask: None,
+            cat_eye: 0.,
+            chromatic_aberration: (0., 0.),
+            focus_dist: None,
+            output: PathBuf::from("result.png"),
+            format: None,
+            display: DisplayTransform::default(),
+            aovs: false,
+            denoiser: Denoiser::None,
+            filter: Filter::default(),
+            sampler: Box::new(SobolSampler::new()),
+            seed: 0,
+            tile_size: 32,
+            tile_order: TileOrder::Spiral,
+            adaptive: None,
+            progressive: false,
+            pass_samples: 1,
+            time_budget: None,
+            snapshot_passes: None,
+            snapshot_interval: None,
+            checkpoint: None,
+            checkpoint_interval: None,
+            resume: false,
+            progress: true,
+            stats: None,
+        }
+    }
+}
+
+fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, key))
+}
+
+fn parse_vec3(key: &str, value: &str) -> Result<Vec3, String> {
+    let parts: Vec<&str> = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if parts.len() != 3 {
+        return Err(format!("{} needs three numbers, got '{}'", key, value));
+    }
+    Ok(Vec3::new(
+        parse(key, parts[0])?,
+        parse(key, parts[1])?,
+        parse(key, parts[2])?,
+    ))
+}
+
+fn parse_seconds(key: &str, value: &str) -> Result<f64, String> {
+    let seconds: f64 = parse(key, value)?;
+    if seconds.is_finite() && seconds >= 0. {
+        Ok(seconds)
+    } else {
+        Err(format!(
+            "{} needs a number of seconds, got '{}'",
+            key, value
+        ))
+    }
+}
+
+fn parse_pair(key: &str, value: &str) -> Result<(f64, f64), String> {
+    let parts: Vec<&str> = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    match parts.as_slice() {
+        [single] => {
+            let v = parse(key, single)?;
+            Ok((v, v))
+        }
+        [low, high] => Ok((parse(key, low)?, parse(key, high)?)),
+        _ => Err(format!("{} needs one or two numbers, got '{}'", key, value)),
+    }
+}
+
+impl Settings {
+    pub fn height(&self) -> u32 {
+        (self.width as f64 / self.aspect_ratio) as u32
+    }
+
+    pub fn focus_dist(&self) -> f64 {
+        self.focus_dist
+            .unwrap_or_else(|| (self.lookfrom - self.lookat).length())
+    }
+
+    /// Parameters of the random scene, `None` for the other scenes.
+    pub fn sphere_field(&self) -> Option<SphereField> {
+        let mut field = SphereField::preset(&self.scene)?;
+        field.seed = self.scene_seed;
+        if let Some(grid) = self.scene_grid {
+            field.grid = grid;
+        }
+        if let Some(materials) = self.scene_materials {
+            field.materials = materials;
+        }
+        if let Some(radius) = self.scene_radius {
+            field.radius = radius;
+        }
+        Some(field)
+    }
+
+    /// Pass settings of a progressive render, `None` to render in one go.
+    /// Pixels are sampled up to `samples`, or `max_samples` with adaptive sampling.
+    /// Checkpointed renders are progressive too, so there is a checkpoint
+    /// to resume from as soon as the first pass is done.
+    pub fn progressive(&self) -> Option<Progressive> {
+        let passes = self.progressive
+            || self.time_budget.is_some()
+            || self.snapshot_passes.is_some()
+            || self.snapshot_interval.is_some()
+            || self.checkpoint.is_some();
+        if !passes {
+            return None;
+        }
+
+        let target = match self.adaptive {
+            Some(adaptive) => adaptive.max_samples.max(self.samples),
+            None => self.samples,
+        };
+        let mut progressive = Progressive::new(self.pass_samples, target);
+        if let Some(budget) = self.time_budget {
+            progressive = progressive.with_time_budget(Duration::from_secs_f64(budget));
+        }
+        if let Some(passes) = self.snapshot_passes {
+            progressive = progressive.with_snapshot_passes(passes);
+        }
+        if let Some(interval) = self.snapshot_interval {
+            progressive = progressive.with_snapshot_interval(Duration::from_secs_f64(interval));
+        }
+        if let Some(interval) = self.checkpoint_interval {
+            progressive = progressive.with_checkpoint_interval(Duration::from_secs_f64(interval));
+        }
+        Some(progressive)
+    }
+
+    /// The integrator named by `integrator`, set up with the other settings.
+    pub fn integrator(&self) -> Box<dyn Integrator + Send + Sync> {
+        let options = IntegratorOptions {
+            roulette_bounces: self.roulette_bounces,
+            ao_distance: self.ao_distance,
+            photons: self.photons,
+            photon_radius: self.photon_radius,
+            mlt_mutations: self.mlt_mutations,
+            mlt_bootstrap: self.mlt_bootstrap,
+            mlt_sigma: self.mlt_sigma,
+            mlt_large_step: self.mlt_large_step,
+        };
+        integrator::by_name(&self.integrator, &options).expect("checked when set")
+    }
+
+    /// The camera chosen by `camera`, looking from `lookfrom` to `lookat`.
+    pub fn camera(&self) -> Box<dyn Camera + Send + Sync> {
+        match self.camera {
+            CameraModel::Perspective => Box::new(
+                PerspectiveCamera::new(
+                    self.lookfrom,
+                    self.lookat,
+                    self.vup,
+                    self.vfov,
+                    self.aspect_ratio,
+                    self.aperture,
+                    self.focus_dist(),
+                )
+                .with_aperture(self.aperture_shape.clone())
+                .with_cat_eye(self.cat_eye)
+                .with_chromatic_aberration(
+                    self.chromatic_aberration.0,
+                    self.chromatic_aberration.1,
+                ),
+            ),
+            CameraModel::Orthographic => Box::new(OrthographicCamera::new(
+                self.lookfrom,
+                self.lookat,
+                self.vup,
+                self.ortho_height,
+                self.aspect_ratio,
+            )),
+            CameraModel::Fisheye(projection) => Box::new(FisheyeCamera::new(
+                self.lookfrom,
+                self.lookat,
+                self.vup,
+                self.fisheye_fov,
+                self.aspect_ratio,
+                projection,
+            )),
+            CameraModel::Equirectangular => Box::new(EquirectangularCamera::new(
+                self.lookfrom,
+                self.lookat,
+                self.vup,
+            )),
+        }
+    }
+
+    // Aperture shape as written in settings, for the image hash.
+    fn aperture_description(&self) -> String {
+        match &self.aperture_shape {
+            Aperture::Circular => String::from("circular"),
+            Aperture::Polygonal { blades, rotation } => format!("{} blades {}", blades, rotation),
+            Aperture::Mask(_) => format!("mask {:?}", self.aperture_mask),
+        }
+    }
+
+    /// Hash of every setting the accumulated film depends on, checkpoints
+    /// only resume with equal hashes. The sample counts, output and
+    /// post-processing are left out as they can change between runs.
+    pub fn image_hash(&self) -> u64 {
+        let description = format!(
+            "{} {:?} {}x{} {:?} {:?} {:?} {:?} {} {} {} {} {} {} {:?} {} {} {} {:?} {} {} {} {} {} {} {} {:?} {} {} {}",
+            self.scene,
+            self.sphere_field(),
+            self.width,
+            self.height(),
+            self.camera,
+            self.lookfrom,
+            self.lookat,
+            self.vup,
+            self.vfov,
+            self.ortho_height,
+            self.fisheye_fov,
+            self.aperture,
+            self.aperture_description(),
+            self.cat_eye,
+            self.chromatic_aberration,
+            self.focus_dist(),
+            self.ray_depth,
+            self.integrator,
+            self.roulette_bounces,
+            self.ao_distance,
+            self.photons,
+            self.photon_radius,
+            self.mlt_mutations,
+            self.mlt_bootstrap,
+            self.mlt_sigma,
+            self.mlt_large_step,
+            self.filter,
+            self.sampler.name(),
+            self.seed,
+            self.aovs || self.denoiser != Denoiser::None,
+        );
+
+        // FNV-1a
+        let mut hash = 0xcbf29ce484222325u64;
+        for byte in description.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    }
+
+    pub fn format(&self) -> ImageFormat {
+        self.format
+            .or_else(|| ImageFormat::from_path(&self.output))
+            .unwrap_or(ImageFormat::Png)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match key.replace('-', "_").as_str() {
+            "scene" => self.scene = value.to_string(),
+            "scene_seed" => self.scene_seed = parse(key, value)?,
+            "scene_grid" => self.scene_grid = Some(parse(key, value)?),
+            "scene_materials" => {
+                let weights = parse_vec3(key, value)?;
+                if weights.x_ < 0. || weights.y_ < 0. || weights.z_ < 0. {
+                    return Err(String::from("scene_materials weights can not be negative"));
+                }
+                if weights.x_ + weights.y_ + weights.z_ <= 0. {
+                    return Err(String::from("scene_materials needs a positive weight"));
+                }
+                self.scene_materials = Some([weights.x_, weights.y_, weights.z_]);
+            }
+            "scene_radius" => {
+                let (low, high) = parse_pair(key, value)?;
+                if low <= 0. || high < low {
+                    return Err(format!("invalid radius range '{}'", value));
+                }
+                self.scene_radius = Some((low, high));
+            }
+            "width" => self.width = parse(key, value)?,
+            "aspect_ratio" => self.aspect_ratio = parse(key, value)?,
+            "samples" => self.samples = parse(key, value)?,
+            "ray_depth" => self.ray_depth = parse(key, value)?,
+            "integrator" => {
+                if integrator::by_name(value, &IntegratorOptions::default()).is_none() {
+                    return Err(format!("unknown integrator '{}'", value));
+                }
+                self.integrator = value.trim().to_ascii_lowercase();
+            }
+            "ao_distance" => self.ao_distance = parse(key, value)?,
+            "photons" => self.photons = parse(key, value)?,
+            "photon_radius" => {
+                let radius: f64 = parse(key, value)?;
+                if radius <= 0. {
+                    return Err(String::from("photon_radius must be positive"));
+                }
+                self.photon_radius = radius;
+            }
+            "mlt_mutations" => self.mlt_mutations = parse(key, value)?,
+            "mlt_bootstrap" => {
+                let paths = parse(key, value)?;
+                if paths == 0 {
+                    return Err(String::from("mlt_bootstrap must be positive"));
+                }
+                self.mlt_bootstrap = paths;
+            }
+            "mlt_sigma" => {
+                let sigma: f64 = parse(key, value)?;
+                if sigma <= 0. {
+                    return Err(String::from("mlt_sigma must be positive"));
+                }
+                self.mlt_sigma = sigma;
+            }
+            "mlt_large_step" => {
+                let probability: f64 = parse(key, value)?;
+                if !(0. ..=1.).contains(&probability) {
+                    return Err(String::from("mlt_large_step must lie between 0 and 1"));
+                }
+                self.mlt_large_step = probability;
+            }
+            "russian_roulette" => {
+                self.roulette_bounces = if parse(key, value)? {
+                    Some(self.roulette_bounces.unwrap_or(3))
+                } else {
+                    None
+                }
+            }
+            "roulette_bounces" => self.roulette_bounces = Some(parse(key, value)?),
+            "camera" => {
+                self.camera = CameraModel::from_name(value)
+                    .ok_or_else(|| format!("unknown camera '{}'", value))?
+            }
+            "lookfrom" => self.lookfrom = parse_vec3(key, value)?,
+            "lookat" => self.lookat = parse_vec3(key, value)?,
+            "vup" => self.vup = parse_vec3(key, value)?,
+            "vfov" => self.vfov = parse(key, value)?,
+            "ortho_height" => {
+                let height: f64 = parse(key, value)?;
+                if height <= 0. {
+                    return Err(String::from("ortho_height must be positive"));
+                }
+                self.ortho_height = height;
+            }
+            "fisheye_fov" => {
+                let fov: f64 = parse(key, value)?;
+                if fov <= 0. || fov > 360. {
+                    return Err(String::from("fisheye_fov must lie between 0 and 360"));
+                }
+                self.fisheye_fov = fov;
+            }
+            "aperture" => self.aperture = parse(key, value)?,
+            "aperture_shape" => {
+                self.aperture_shape = match value.to_ascii_lowercase().as_str() {
+                    "circular" => Aperture::Circular,
+                    "polygonal" => match self.aperture_shape {
+                        Aperture::Polygonal { .. } => self.aperture_shape.clone(),
+                        _ => Aperture::Polygonal {
+                            blades: 6,
+                            rotation: 0.,
+                        },
+                    },
+                    "mask" => match self.aperture_shape {
+                        Aperture::Mask(_) => self.aperture_shape.clone(),
+                        _ => return Err(String::from("aperture_shape mask needs aperture_mask")),
+                    },
+                    _ => return Err(format!("unknown aperture shape '{}'", value)),
+                }
+            }
+            "aperture_blades" => {
+                let blades = parse(key, value)?;
+                if blades < 3 {
+                    return Err(String::from("aperture_blades needs at least 3 blades"));
+                }
+                let rotation = match self.aperture_shape {
+                    Aperture::Polygonal { rotation, .. } => rotation,
+                    _ => 0.,
+                };
+                self.aperture_shape = Aperture::Polygonal { blades, rotation };
+            }
+            "aperture_rotation" => {
+                let rotation = parse(key, value)?;
+                let blades = match self.aperture_shape {
+                    Aperture::Polygonal { blades, .. } => blades,
+                    _ => 6,
+                };
+                self.aperture_shape = Aperture::Polygonal { blades, rotation };
+            }
+            "aperture_mask" => {
+                let image = image::open(value)
+                    .map_err(|why| format!("unable to read aperture mask {} : {}", value, why))?;
+                let mask = ApertureMask::from_image(&image)
+                    .ok_or_else(|| format!("aperture mask {} is completely black", value))?;
+                self.aperture_shape = Aperture::Mask(mask);
+                self.aperture_mask = Some(PathBuf::from(value));
+            }
+            "cat_eye" => self.cat_eye = parse(key, value)?,
+            "chromatic_aberration" => self.chromatic_aberration = parse_pair(key, value)?,
+            "focus_dist" => self.focus_dist = Some(parse(key, value)?),
+            "output" => self.output = PathBuf::from(value),
+            "format" => {
+                self.format = Some(
+                    ImageFormat::from_name(value)
+                        .ok_or_else(|| format!("unknown image format '{}'", value))?,
+                )
+            }
+            "exposure" => self.display.exposure = parse(key, value)?,
+            "tone_map" => {
+                let white = match self.display.tone_map {
+                    ToneMap::ExtendedReinhard { white } => Some(white),
+                    _ => None,
+                };
+                self.display.tone_map = ToneMap::from_name(value)
+                    .ok_or_else(|| format!("unknown tone map '{}'", value))?;
+                if let (ToneMap::ExtendedReinhard { .. }, Some(white)) =
+                    (self.display.tone_map, white)
+                {
+                    self.display.tone_map = ToneMap::ExtendedReinhard { white };
+                }
+            }
+            "white_point" => {
+                self.display.tone_map = ToneMap::ExtendedReinhard {
+                    white: parse(key, value)?,
+                }
+            }
+            "aovs" => self.aovs = parse(key, value)?,
+            "denoise" => {
+                self.denoiser = Denoiser::from_name(value)
+                    .ok_or_else(|| format!("unknown denoiser '{}'", value))?
+            }
+            "denoise_iterations" => match &mut self.denoiser {
+                Denoiser::ATrous(atrous) => atrous.iterations = parse(key, value)?,
+                Denoiser::None => return Err(String::from("denoise_iterations needs a denoiser")),
+            },
+            "filter" => {
+                self.filter = Filter::from_name(value, None)
+                    .ok_or_else(|| format!("unknown filter '{}'", value))?
+            }
+            "filter_radius" => {
+                let radius: f64 = parse(key, value)?;
+                if radius <= 0. {
+                    return Err(String::from("filter_radius must be positive"));
+                }
+                self.filter = match self.filter {
+                    Filter::Box { .. } => Filter::Box { radius },
+                    Filter::Tent { .. } => Filter::Tent { radius },
+                    Filter::Gaussian { alpha, .. } => Filter::Gaussian { radius, alpha },
+                    Filter::Mitchell { b, c, .. } => Filter::Mitchell { radius, b, c },
+                    Filter::Lanczos { tau, .. } => Filter::Lanczos { radius, tau },
+                    Filter::BlackmanHarris { .. } => Filter::BlackmanHarris { radius },
+                }
+            }
+            "sampler" => {
+                self.sampler =
+                    sampler::by_name(value).ok_or_else(|| format!("unknown sampler '{}'", value))?
+            }
+            "seed" => self.seed = parse(key, value)?,
+            "tile_size" => {
+                self.tile_size = parse(key, value)?;
+                if self.tile_size == 0 {
+                    return Err(String::from("tile_size must be positive"));
+                }
+            }
+            "tile_order" => {
+                self.tile_order = TileOrder::from_name(value)
+                    .ok_or_else(|| format!("unknown tile order '{}'", value))?
+            }
+            "adaptive" => {
+                self.adaptive = if parse(key, value)? {
+                    Some(self.adaptive.unwrap_or_default())
+                } else {
+                    None
+                }
+            }
+            "adaptive_threshold" => {
+                self.adaptive.get_or_insert_with(Default::default).threshold = parse(key, value)?
+            }
+            "max_samples" => {
+                self.adaptive
+                    .get_or_insert_with(Default::default)
+                    .max_samples = parse(key, value)?
+            }
+            "checkpoint" => self.checkpoint = Some(PathBuf::from(value)),
+            "checkpoint_interval" => self.checkpoint_interval = Some(parse_seconds(key, value)?),
+            "resume" => self.resume = parse(key, value)?,
+            "progress" => self.progress = parse(key, value)?,
+            "stats" => {
+                self.stats = match value.trim() {
+                    "none" => None,
+                    name => Some(
+                        StatsFormat::from_name(name)
+                            .ok_or_else(|| format!("unknown stats format '{}'", value))?,
+                    ),
+                }
+            }
+            "progressive" => self.progressive = parse(key, value)?,
+            "pass_samples" => self.pass_samples = parse(key, value)?,
+            "time_budget" => self.time_budget = Some(parse_seconds(key, value)?),
+            "snapshot_passes" => self.snapshot_passes = Some(parse(key, value)?),
+            "snapshot_interval" => self.snapshot_interval = Some(parse_seconds(key, value)?),
+            _ => return Err(format!("unknown setting '{}'", key)),
+        }
+        Ok(())
+    }
+
+    /// Applies every `key = value` line of a scene file.
+    pub fn load(&mut self, path: &Path) -> Result<(), String> {
+        let content = fs::read_to_string(path)
+            .map_err(|why| format!("unable to read {} : {}", path.display(), why))?;
+
+        for (number, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => (key.trim(), value),
+                _ => {
+                    return Err(format!(
+                        "{}:{}: expected key = value",
+                        path.display(),
+                        number + 1
+                    ))
+                }
+            };
+            self.set(key, value)
+                .map_err(|why| format!("{}:{}: {}", path.display(), number + 1, why))?;
+        }
+        Ok(())
+    }
+
+    /// Applies command line arguments in order, so later ones win.
+    /// Adaptive sampling is refused for integrators that only splat, and
+    /// random scenes with more than `MAX_SCENE_GRID` cells on a side.
+    /// `--scene-file FILE` loads a scene file at that point,
+    /// `-o` and `-f` are short for `--output` and `--format`.
+    pub fn apply_args<I: Iterator<Item = String>>(&mut self, mut args: I) -> Result<(), String> {
+        while let Some(arg) = args.next() {
+            let key = match arg.as_str() {
+                "-o" => "output",
+                "-f" => "format",
+                _ if arg.starts_with("--") => &arg[2..],
+                _ => return Err(format!("unexpected argument '{}'", arg)),
+            };
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for '{}'", arg))?;
+
+            if key == "scene-file" || key == "scene_file" {
+                self.load(Path::new(&value))?;
+            } else {
+                self.set(key, &value)?;
+            }
+        }
+        // Checked once all keys are in, they may come in any order.
+        if let Some(grid) = self.scene_grid {
+            if grid > MAX_SCENE_GRID {
+                return Err(format!(
+                    "scene_grid {} is too large, at most {} is allowed",
+                    grid, MAX_SCENE_GRID
+                ));
+            }
+        }
+        if self.adaptive.is_some() && self.integrator().splats_only() {
+            return Err(format!(
+                "adaptive sampling does not work with the {} integrator",
+                self.integrator
+            ));
+        }
+        Ok(())
+    }
+}
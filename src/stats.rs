@@ -0,0 +1,187 @@
+//! Opt-in counters of the work a render does, to compare scenes and
+//! optimisations with.
+//!
+//! Counting happens in thread locals and only on threads that turned it on,
+//! the render workers when `Viewport::with_stats` is set. Every worker hands
+//! its counts over after each tile, where they are added up. While no thread
+//! counts, recording only reads a global flag.
+
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// How the statistics are printed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StatsFormat {
+    /// Aligned table for reading.
+    Summary,
+    /// One line JSON object for scripts.
+    Json,
+}
+
+impl StatsFormat {
+    pub fn from_name(name: &str) -> Option<StatsFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "summary" => Some(StatsFormat::Summary),
+            "json" => Some(StatsFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Totals of a render, or of all renders of a viewport.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct RenderStats {
+    /// Rays sent from the camera.
+    pub camera_rays: u64,
+    /// Every ray traced through the scene, camera and shadow rays included.
+    pub rays: u64,
+    /// Rays only testing whether a point is visible.
+    pub shadow_rays: u64,
+    /// Nodes of heightfield quadtrees visited, the rest of the scene is a
+    /// plain list.
+    pub heightfield_nodes: u64,
+    /// Ray intersection tests with spheres, cuboids, triangles and SDFs.
+    pub primitive_tests: u64,
+    pub paths: u64,
+    /// Bounces of all paths added up.
+    pub path_depth_sum: u64,
+    /// Paths still going when the ray depth ran out.
+    pub terminated_by_depth: u64,
+    /// Paths ended by Russian roulette.
+    pub terminated_by_roulette: u64,
+    /// Wall clock time spent rendering.
+    pub render_time: Duration,
+}
+
+impl RenderStats {
+    pub fn merge(&mut self, other: &RenderStats) {
+        self.camera_rays += other.camera_rays;
+        self.rays += other.rays;
+        self.shadow_rays += other.shadow_rays;
+        self.heightfield_nodes += other.heightfield_nodes;
+        self.primitive_tests += other.primitive_tests;
+        self.paths += other.paths;
+        self.path_depth_sum += other.path_depth_sum;
+        self.terminated_by_depth += other.terminated_by_depth;
+        self.terminated_by_roulette += other.terminated_by_roulette;
+        self.render_time += other.render_time;
+    }
+
+    pub fn average_path_depth(&self) -> f64 {
+        if self.paths == 0 {
+            0.
+        } else {
+            self.path_depth_sum as f64 / self.paths as f64
+        }
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        let seconds = self.render_time.as_secs_f64();
+        if seconds > 0. {
+            self.rays as f64 / seconds
+        } else {
+            0.
+        }
+    }
+
+    /// The counters and derived rates as a single JSON object.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"camera_rays\": {}, \"rays\": {}, \"shadow_rays\": {}, \
+             \"heightfield_nodes\": {}, \"primitive_tests\": {}, \"paths\": {}, \
+             \"average_path_depth\": {}, \"terminated_by_depth\": {}, \
+             \"terminated_by_roulette\": {}, \
+             \"render_seconds\": {}, \"rays_per_second\": {}}}",
+            self.camera_rays,
+            self.rays,
+            self.shadow_rays,
+            self.heightfield_nodes,
+            self.primitive_tests,
+            self.paths,
+            self.average_path_depth(),
+            self.terminated_by_depth,
+            self.terminated_by_roulette,
+            self.render_time.as_secs_f64(),
+            self.rays_per_second()
+        )
+    }
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let per_ray = |count: u64| {
+            if self.rays == 0 {
+                0.
+            } else {
+                count as f64 / self.rays as f64
+            }
+        };
+        writeln!(
+            f,
+            "Render time          {:.2} s",
+            self.render_time.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "Rays                 {} ({:.2} M/s)",
+            self.rays,
+            self.rays_per_second() / 1e6
+        )?;
+        writeln!(f, "  camera             {}", self.camera_rays)?;
+        writeln!(f, "  shadow             {}", self.shadow_rays)?;
+        writeln!(
+            f,
+            "Heightfield nodes    {} ({:.2} per ray)",
+            self.heightfield_nodes,
+            per_ray(self.heightfield_nodes)
+        )?;
+        writeln!(
+            f,
+            "Primitive tests      {} ({:.2} per ray)",
+            self.primitive_tests,
+            per_ray(self.primitive_tests)
+        )?;
+        writeln!(f, "Paths                {}", self.paths)?;
+        writeln!(f, "  average depth      {:.3}", self.average_path_depth())?;
+        writeln!(f, "  ended by max depth {}", self.terminated_by_depth)?;
+        write!(f, "  ended by roulette  {}", self.terminated_by_roulette)
+    }
+}
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static LOCAL: Cell<RenderStats> = Cell::new(RenderStats::default());
+}
+
+// Threads that count right now.
+static COUNTING: AtomicUsize = AtomicUsize::new(0);
+
+/// Turns counting on or off for the calling thread.
+pub(crate) fn set_enabled(enabled: bool) {
+    if ENABLED.with(|cell| cell.replace(enabled)) != enabled {
+        if enabled {
+            COUNTING.fetch_add(1, Ordering::Relaxed);
+        } else {
+            COUNTING.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Updates the counts of the calling thread, if it counts.
+#[inline]
+pub(crate) fn record<F: FnOnce(&mut RenderStats)>(update: F) {
+    if COUNTING.load(Ordering::Relaxed) > 0 && ENABLED.with(|cell| cell.get()) {
+        LOCAL.with(|cell| {
+            let mut stats = cell.get();
+            update(&mut stats);
+            cell.set(stats);
+        });
+    }
+}
+
+/// Counts of the calling thread since the last call.
+pub(crate) fn take() -> RenderStats {
+    LOCAL.with(|cell| cell.replace(RenderStats::default()))
+}
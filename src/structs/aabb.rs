@@ -0,0 +1,72 @@
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    pub fn new(a: Point3, b: Point3) -> Aabb {
+        Aabb {
+            min: Point3::new(a.x_.min(b.x_), a.y_.min(b.y_), a.z_.min(b.z_)),
+            max: Point3::new(a.x_.max(b.x_), a.y_.max(b.y_), a.z_.max(b.z_)),
+        }
+    }
+
+    pub fn center(&self) -> Point3 {
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn half_size(&self) -> Vec3 {
+        0.5 * (self.max - self.min)
+    }
+
+    /// Smallest box holding both boxes.
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3::new(
+                self.min.x_.min(other.min.x_),
+                self.min.y_.min(other.min.y_),
+                self.min.z_.min(other.min.z_),
+            ),
+            max: Point3::new(
+                self.max.x_.max(other.max.x_),
+                self.max.y_.max(other.max.y_),
+                self.max.z_.max(other.max.z_),
+            ),
+        }
+    }
+
+    // Slab test over the whole line, so the entry may lie behind the origin.
+    pub fn intersect(&self, r: &Ray) -> Option<(f64, f64)> {
+        let origin = [r.origin().x_, r.origin().y_, r.origin().z_];
+        let direction = [r.direction().x_, r.direction().y_, r.direction().z_];
+        let min = [self.min.x_, self.min.y_, self.min.z_];
+        let max = [self.max.x_, self.max.y_, self.max.z_];
+
+        let mut t_enter = f64::NEG_INFINITY;
+        let mut t_exit = f64::INFINITY;
+        for axis in 0..3 {
+            let inv_d = 1. / direction[axis];
+            let mut t0 = (min[axis] - origin[axis]) * inv_d;
+            let mut t1 = (max[axis] - origin[axis]) * inv_d;
+            if inv_d < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN appears for rays parallel to a slab and lying on its plane, skip them.
+            if !t0.is_nan() {
+                t_enter = t_enter.max(t0);
+            }
+            if !t1.is_nan() {
+                t_exit = t_exit.min(t1);
+            }
+            if t_exit <= t_enter {
+                return None;
+            }
+        }
+
+        Some((t_enter, t_exit))
+    }
+}
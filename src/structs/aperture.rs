@@ -0,0 +1,116 @@
+use crate::sampler::warp;
+
+use std::f64::consts::PI;
+
+/// Shape of the lens opening, it decides how out of focus highlights look.
+#[derive(Clone)]
+pub enum Aperture {
+    Circular,
+    /// Diaphragm made of straight blades, `rotation` in degrees.
+    Polygonal {
+        blades: u32,
+        rotation: f64,
+    },
+    Mask(ApertureMask),
+}
+
+impl Aperture {
+    /// Area of the shape `sample` covers uniformly, `None` for masks whose
+    /// points are not equally likely.
+    pub fn area(&self) -> Option<f64> {
+        match self {
+            Aperture::Circular => Some(PI),
+            Aperture::Polygonal { blades, .. } => {
+                let blades = (*blades).max(3) as f64;
+                Some(blades / 2. * (2. * PI / blades).sin())
+            }
+            Aperture::Mask(_) => None,
+        }
+    }
+
+    /// Point on the aperture, inside the `[-1, 1]` square, for the uniform
+    /// sample `u`.
+    pub fn sample(&self, u: (f64, f64)) -> (f64, f64) {
+        match self {
+            Aperture::Circular => warp::concentric_disk(u),
+            Aperture::Polygonal { blades, rotation } => {
+                let blades = (*blades).max(3);
+                let step = 2. * PI / blades as f64;
+                // The first coordinate picks the blade and is reused inside it.
+                let scaled = u.0 * blades as f64;
+                let blade = scaled.floor().min((blades - 1) as f64);
+                let start = rotation.to_radians() + blade * step;
+
+                // Uniform point in the triangle spanned by the center and one edge.
+                let (mut a, mut b) = (scaled - blade, u.1);
+                if a + b > 1. {
+                    a = 1. - a;
+                    b = 1. - b;
+                }
+                (
+                    a * start.cos() + b * (start + step).cos(),
+                    a * start.sin() + b * (start + step).sin(),
+                )
+            }
+            Aperture::Mask(mask) => mask.sample(u),
+        }
+    }
+}
+
+/// Aperture shape taken from an image, brighter pixels let through more light.
+#[derive(Clone)]
+pub struct ApertureMask {
+    width: usize,
+    height: usize,
+    // Cumulative pixel weights, row by row.
+    cdf: Vec<f64>,
+}
+
+impl ApertureMask {
+    /// `None` for a completely black image, which lets no light through.
+    pub fn from_image(img: &image::DynamicImage) -> Option<ApertureMask> {
+        let gray = img.to_luma16();
+        let mut cdf = Vec::with_capacity((gray.width() * gray.height()) as usize);
+        let mut total = 0.;
+        for pixel in gray.pixels() {
+            total += pixel[0] as f64;
+            cdf.push(total);
+        }
+        if total <= 0. {
+            return None;
+        }
+
+        Some(ApertureMask {
+            width: gray.width() as usize,
+            height: gray.height() as usize,
+            cdf: cdf.iter().map(|c| c / total).collect(),
+        })
+    }
+
+    fn sample(&self, (pick, jitter_y): (f64, f64)) -> (f64, f64) {
+        let index = self
+            .cdf
+            .binary_search_by(|c| c.partial_cmp(&pick).unwrap())
+            .unwrap_or_else(|i| i)
+            .min(self.cdf.len() - 1);
+
+        // Where the pick fell inside the chosen pixel's share of the CDF.
+        let below = if index == 0 { 0. } else { self.cdf[index - 1] };
+        let share = self.cdf[index] - below;
+        let jitter_x = if share > 0. {
+            num::clamp((pick - below) / share, 0., 1.)
+        } else {
+            0.5
+        };
+
+        let x = (index % self.width) as f64 + jitter_x;
+        let y = (index / self.width) as f64 + jitter_y;
+        let size = self.width.max(self.height) as f64;
+
+        // Keep the aspect of the mask and flip rows so the image is upright.
+        (
+            (2. * x - self.width as f64) / size,
+            (self.height as f64 - 2. * y) / size,
+        )
+    }
+}
@@ -0,0 +1,490 @@
+use crate::sampler::Sampler;
+use crate::structs::aperture::Aperture;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+use std::f64::consts::PI;
+
+/// Point on the lens that light from somewhere in the scene reaches the
+/// film through.
+#[derive(Debug, Copy, Clone)]
+pub struct ImportanceSample {
+    pub lens_point: Point3,
+    /// Film position `(s, t)` the light lands at.
+    pub film_position: (f64, f64),
+    /// Importance arriving at the scene point, over the density of the
+    /// lens point seen from there.
+    pub weight: f64,
+}
+
+pub trait Camera {
+    /// Ray through the film position `(s, t)`, both in `[0, 1]` starting at
+    /// the bottom left corner. `None` when the position lies outside of
+    /// the area the projection covers. Lens positions come from `sampler`.
+    fn send_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray>;
+
+    /// Same as `send_ray`, together with the per channel weight the radiance
+    /// along the ray contributes with.
+    fn send_weighted_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<(Ray, Vec3)> {
+        self.send_ray(s, t, sampler)
+            .map(|r| (r, Vec3::new(1., 1., 1.)))
+    }
+
+    /// Film position `(s, t)` the point is seen at through the center of the lens.
+    fn project(&self, _point: Point3) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// Picks a point on the lens for light leaving `point` towards the
+    /// camera, for integrators tracing light from the lights. `None` when
+    /// the point is not seen or the camera cannot be reached that way.
+    fn sample_importance(&self, _point: &Point3, _u: (f64, f64)) -> Option<ImportanceSample> {
+        None
+    }
+
+    /// Densities `send_ray` picks `r` with over uniform film positions:
+    /// per area of its origin on the lens and per solid angle of its
+    /// direction. `None` for cameras without `sample_importance`.
+    fn importance_pdf(&self, _r: &Ray) -> Option<(f64, f64)> {
+        None
+    }
+}
+
+// Film position of `point` seen from `origin` on the film plane given by its
+// corner and the two spanning vectors.
+fn project_on_plane(
+    origin: Point3,
+    point: Point3,
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+) -> Option<(f64, f64)> {
+    let normal = Vec3::cross(&horizontal, &vertical);
+    let direction = point - origin;
+    let along = Vec3::dot(&direction, &normal);
+    if along == 0. {
+        return None;
+    }
+    let k = Vec3::dot(&(lower_left_corner - origin), &normal) / along;
+    if k <= 0. {
+        return None;
+    }
+
+    let on_plane = origin + k * direction - lower_left_corner;
+    Some((
+        Vec3::dot(&on_plane, &horizontal) / Vec3::dot(&horizontal, &horizontal),
+        Vec3::dot(&on_plane, &vertical) / Vec3::dot(&vertical, &vertical),
+    ))
+}
+
+/// Orthonormal basis looking from `lookfrom` to `lookat`, `w` points backwards.
+#[derive(Debug, Copy, Clone)]
+struct Frame {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Frame {
+    fn new(lookfrom: Point3, lookat: Point3, vup: Vec3) -> Frame {
+        let w = Vec3::unit_vector(lookfrom - lookat);
+        let u = Vec3::unit_vector(Vec3::cross(&vup, &w));
+        let v = Vec3::cross(&w, &u);
+        Frame { u, v, w }
+    }
+}
+
+/// Thin lens camera with depth of field.
+pub struct PerspectiveCamera {
+    lens_radius_: f64,
+    origin_: Point3,
+    horizontal_: Vec3,
+    vertical_: Vec3,
+    lower_left_corner_: Vec3,
+    u_: Vec3,
+    v_: Vec3,
+    aperture_: Aperture,
+    cat_eye_: f64,
+    // Longitudinal and lateral chromatic aberration.
+    chromatic_: (f64, f64),
+}
+
+impl PerspectiveCamera {
+    pub fn new(
+        lookfrom: Point3,
+        lookat: Point3,
+        vup: Vec3,
+        vfov: f64,
+        aspect_ratio: f64,
+        aperture: f64,
+        focus_dist: f64,
+    ) -> PerspectiveCamera {
+        let theta = vfov.to_radians();
+        let h = (theta / 2.).tan();
+        let view_height = h;
+        let view_width = aspect_ratio * view_height;
+
+        let Frame { u, v, w } = Frame::new(lookfrom, lookat, vup);
+
+        PerspectiveCamera {
+            lens_radius_: aperture / 2.,
+            origin_: lookfrom,
+            horizontal_: 2. * focus_dist * view_width * u,
+            vertical_: 2. * focus_dist * view_height * v,
+            lower_left_corner_: (lookfrom
+                - view_width * focus_dist * u
+                - view_height * focus_dist * v
+                - focus_dist * w),
+            u_: u,
+            v_: v,
+            aperture_: Aperture::Circular,
+            cat_eye_: 0.,
+            chromatic_: (0., 0.),
+        }
+    }
+
+    pub fn with_aperture(mut self, aperture: Aperture) -> PerspectiveCamera {
+        self.aperture_ = aperture;
+        self
+    }
+
+    /// Off axis the lens barrel cuts into the aperture, turning bokeh into
+    /// cat eye shapes towards the image corners and darkening them.
+    /// `strength` is the shift of the clipping circle at the film edge,
+    /// in aperture radii.
+    pub fn with_cat_eye(mut self, strength: f64) -> PerspectiveCamera {
+        self.cat_eye_ = strength;
+        self
+    }
+
+    /// Red and blue focus `longitudinal` times the focus distance
+    /// behind and in front of green, and are magnified by `lateral`.
+    pub fn with_chromatic_aberration(
+        mut self,
+        longitudinal: f64,
+        lateral: f64,
+    ) -> PerspectiveCamera {
+        self.chromatic_ = (longitudinal, lateral);
+        self
+    }
+
+    // `shift` selects the wavelength for chromatic aberration, -1 for red to 1 for blue.
+    fn lens_ray(&self, s: f64, t: f64, shift: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        let (lx, ly) = self.aperture_.sample(sampler.get_2d());
+
+        if self.cat_eye_ != 0. {
+            let cx = lx - self.cat_eye_ * (2. * s - 1.);
+            let cy = ly - self.cat_eye_ * (2. * t - 1.);
+            if cx * cx + cy * cy > 1. {
+                return None;
+            }
+        }
+
+        let offset = self.lens_radius_ * (lx * self.u_ + ly * self.v_);
+        let magnification = 1. + shift * self.chromatic_.1;
+        let s = 0.5 + (s - 0.5) * magnification;
+        let t = 0.5 + (t - 0.5) * magnification;
+        let focus = self.lower_left_corner_ + s * self.horizontal_ + t * self.vertical_;
+        let focus = self.origin_ + (1. - shift * self.chromatic_.0) * (focus - self.origin_);
+
+        Some(Ray::new(
+            self.origin_ + offset,
+            focus - self.origin_ - offset,
+        ))
+    }
+
+    // Area the lens points are spread over, 1 for a pinhole. `None` where
+    // the lens is not sampled uniformly or rays depend on the film
+    // position, which importance does not account for.
+    fn lens_area(&self) -> Option<f64> {
+        if self.cat_eye_ != 0. || self.chromatic_ != (0., 0.) {
+            return None;
+        }
+        if self.lens_radius_ == 0. {
+            return Some(1.);
+        }
+        self.aperture_
+            .area()
+            .map(|area| area * self.lens_radius_ * self.lens_radius_)
+    }
+
+    // Viewing direction, the distance of the focus plane and its area.
+    fn focus_plane(&self) -> (Vec3, f64, f64) {
+        let normal = Vec3::cross(&self.horizontal_, &self.vertical_);
+        let forward = -Vec3::unit_vector(normal);
+        let distance = Vec3::dot(&(self.lower_left_corner_ - self.origin_), &forward);
+        (forward, distance, normal.length())
+    }
+
+    // Film position the ray crosses the focus plane at, `None` off the film.
+    fn film_position(&self, r: &Ray) -> Option<(f64, f64)> {
+        let (s, t) = project_on_plane(
+            r.origin(),
+            r.origin() + r.direction(),
+            self.lower_left_corner_,
+            self.horizontal_,
+            self.vertical_,
+        )?;
+        if (0. ..=1.).contains(&s) && (0. ..=1.).contains(&t) {
+            Some((s, t))
+        } else {
+            None
+        }
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn send_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        self.lens_ray(s, t, 0., sampler)
+    }
+
+    fn send_weighted_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<(Ray, Vec3)> {
+        if self.chromatic_ == (0., 0.) {
+            return self
+                .send_ray(s, t, sampler)
+                .map(|r| (r, Vec3::new(1., 1., 1.)));
+        }
+
+        // Trace a single channel and weight it so the average stays the same.
+        let (shift, weight) = match (sampler.get_1d() * 3.) as u32 {
+            0 => (-1., Vec3::new(3., 0., 0.)),
+            1 => (0., Vec3::new(0., 3., 0.)),
+            _ => (1., Vec3::new(0., 0., 3.)),
+        };
+        self.lens_ray(s, t, shift, sampler).map(|r| (r, weight))
+    }
+
+    fn project(&self, point: Point3) -> Option<(f64, f64)> {
+        project_on_plane(
+            self.origin_,
+            point,
+            self.lower_left_corner_,
+            self.horizontal_,
+            self.vertical_,
+        )
+    }
+
+    fn sample_importance(&self, point: &Point3, u: (f64, f64)) -> Option<ImportanceSample> {
+        self.lens_area()?;
+        let (lx, ly) = self.aperture_.sample(u);
+        let lens_point = self.origin_ + self.lens_radius_ * (lx * self.u_ + ly * self.v_);
+        let to_point = *point - lens_point;
+        let film_position = self.film_position(&Ray::new(lens_point, to_point))?;
+
+        let (forward, distance, area) = self.focus_plane();
+        let length = to_point.length();
+        let cos = Vec3::dot(&to_point, &forward) / length;
+        if cos <= 0. {
+            return None;
+        }
+        // Importance is normalized over the whole film, the lens area
+        // cancels against the density of the lens point.
+        Some(ImportanceSample {
+            lens_point,
+            film_position,
+            weight: distance * distance / (area * cos.powi(3) * length * length),
+        })
+    }
+
+    fn importance_pdf(&self, r: &Ray) -> Option<(f64, f64)> {
+        let lens_area = self.lens_area()?;
+        let (forward, distance, area) = self.focus_plane();
+        let cos = Vec3::dot(&Vec3::unit_vector(r.direction()), &forward);
+        if cos <= 0. || self.film_position(r).is_none() {
+            return Some((1. / lens_area, 0.));
+        }
+        Some((1. / lens_area, distance * distance / (area * cos.powi(3))))
+    }
+}
+
+/// Parallel projection, `view_height` is the height of the film in world units.
+pub struct OrthographicCamera {
+    lower_left_corner_: Point3,
+    horizontal_: Vec3,
+    vertical_: Vec3,
+    direction_: Vec3,
+}
+
+impl OrthographicCamera {
+    pub fn new(
+        lookfrom: Point3,
+        lookat: Point3,
+        vup: Vec3,
+        view_height: f64,
+        aspect_ratio: f64,
+    ) -> OrthographicCamera {
+        let Frame { u, v, w } = Frame::new(lookfrom, lookat, vup);
+        let horizontal = aspect_ratio * view_height * u;
+        let vertical = view_height * v;
+
+        OrthographicCamera {
+            lower_left_corner_: lookfrom - horizontal / 2. - vertical / 2.,
+            horizontal_: horizontal,
+            vertical_: vertical,
+            direction_: -w,
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn send_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        Some(Ray::new(
+            self.lower_left_corner_ + s * self.horizontal_ + t * self.vertical_,
+            self.direction_,
+        ))
+    }
+
+    fn project(&self, point: Point3) -> Option<(f64, f64)> {
+        // Move the point back onto the film plane along the viewing direction.
+        let normal = Vec3::cross(&self.horizontal_, &self.vertical_);
+        let back = Vec3::dot(&(point - self.lower_left_corner_), &normal)
+            / Vec3::dot(&self.direction_, &normal);
+        let on_plane = point - back * self.direction_ - self.lower_left_corner_;
+        Some((
+            Vec3::dot(&on_plane, &self.horizontal_)
+                / Vec3::dot(&self.horizontal_, &self.horizontal_),
+            Vec3::dot(&on_plane, &self.vertical_) / Vec3::dot(&self.vertical_, &self.vertical_),
+        ))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FisheyeProjection {
+    /// Distance from the image center is proportional to the angle.
+    Equidistant,
+    /// Equal solid angles cover equal image areas.
+    Equisolid,
+}
+
+/// Fisheye lens with a circular image touching the top and bottom of the film.
+pub struct FisheyeCamera {
+    origin_: Point3,
+    frame_: Frame,
+    half_fov_: f64,
+    aspect_ratio_: f64,
+    projection_: FisheyeProjection,
+}
+
+impl FisheyeCamera {
+    /// `fov` is the angle in degrees covered by the image circle, up to 360.
+    pub fn new(
+        lookfrom: Point3,
+        lookat: Point3,
+        vup: Vec3,
+        fov: f64,
+        aspect_ratio: f64,
+        projection: FisheyeProjection,
+    ) -> FisheyeCamera {
+        FisheyeCamera {
+            origin_: lookfrom,
+            frame_: Frame::new(lookfrom, lookat, vup),
+            half_fov_: fov.to_radians() / 2.,
+            aspect_ratio_: aspect_ratio,
+            projection_: projection,
+        }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn send_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        let x = (2. * s - 1.) * self.aspect_ratio_;
+        let y = 2. * t - 1.;
+        let radius = (x * x + y * y).sqrt();
+        if radius > 1. {
+            return None;
+        }
+
+        let theta = match self.projection_ {
+            FisheyeProjection::Equidistant => radius * self.half_fov_,
+            FisheyeProjection::Equisolid => 2. * (radius * (self.half_fov_ / 2.).sin()).asin(),
+        };
+        let phi = y.atan2(x);
+        let Frame { u, v, w } = self.frame_;
+
+        Some(Ray::new(
+            self.origin_,
+            theta.sin() * phi.cos() * u + theta.sin() * phi.sin() * v - theta.cos() * w,
+        ))
+    }
+
+    fn project(&self, point: Point3) -> Option<(f64, f64)> {
+        let Frame { u, v, w } = self.frame_;
+        let direction = Vec3::unit_vector(point - self.origin_);
+        let theta = num::clamp(-Vec3::dot(&direction, &w), -1., 1.).acos();
+        if theta > self.half_fov_ {
+            return None;
+        }
+
+        let radius = match self.projection_ {
+            FisheyeProjection::Equidistant => theta / self.half_fov_,
+            FisheyeProjection::Equisolid => (theta / 2.).sin() / (self.half_fov_ / 2.).sin(),
+        };
+        let phi = Vec3::dot(&direction, &v).atan2(Vec3::dot(&direction, &u));
+        Some((
+            (radius * phi.cos() / self.aspect_ratio_ + 1.) / 2.,
+            (radius * phi.sin() + 1.) / 2.,
+        ))
+    }
+}
+
+/// Full 360 by 180 degree panorama in latitude-longitude layout.
+pub struct EquirectangularCamera {
+    origin_: Point3,
+    frame_: Frame,
+}
+
+impl EquirectangularCamera {
+    /// `lookat` ends up in the middle of the image.
+    pub fn new(lookfrom: Point3, lookat: Point3, vup: Vec3) -> EquirectangularCamera {
+        EquirectangularCamera {
+            origin_: lookfrom,
+            frame_: Frame::new(lookfrom, lookat, vup),
+        }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn send_ray(&self, s: f64, t: f64, _sampler: &mut dyn Sampler) -> Option<Ray> {
+        let longitude = (s - 0.5) * 2. * PI;
+        let latitude = (t - 0.5) * PI;
+        let Frame { u, v, w } = self.frame_;
+
+        Some(Ray::new(
+            self.origin_,
+            latitude.cos() * longitude.sin() * u + latitude.sin() * v
+                - latitude.cos() * longitude.cos() * w,
+        ))
+    }
+
+    fn project(&self, point: Point3) -> Option<(f64, f64)> {
+        let Frame { u, v, w } = self.frame_;
+        let direction = Vec3::unit_vector(point - self.origin_);
+        let latitude = num::clamp(Vec3::dot(&direction, &v), -1., 1.).asin();
+        let longitude = Vec3::dot(&direction, &u).atan2(-Vec3::dot(&direction, &w));
+        Some((longitude / (2. * PI) + 0.5, latitude / PI + 0.5))
+    }
+}
+
+/// Projection the camera is built with, as named in settings.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CameraModel {
+    Perspective,
+    Orthographic,
+    Fisheye(FisheyeProjection),
+    Equirectangular,
+}
+
+impl CameraModel {
+    pub fn from_name(name: &str) -> Option<CameraModel> {
+        match name.to_ascii_lowercase().as_str() {
+            "perspective" => Some(CameraModel::Perspective),
+            "orthographic" => Some(CameraModel::Orthographic),
+            "fisheye_equidistant" | "fisheye" => {
+                Some(CameraModel::Fisheye(FisheyeProjection::Equidistant))
+            }
+            "fisheye_equisolid" => Some(CameraModel::Fisheye(FisheyeProjection::Equisolid)),
+            "equirectangular" | "panorama" => Some(CameraModel::Equirectangular),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::structs::aabb::Aabb;
+use crate::structs::hitable::{HitRecord, Hitable};
+use crate::structs::material::Material;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::Vec3;
+
+/// Point where a ray crosses the boundary of a closed object.
+/// `normal` always points out of the object.
+#[derive(Clone, Copy)]
+pub struct Crossing {
+    pub t: f64,
+    pub normal: Vec3,
+    pub material: Material,
+}
+
+/// Part of the ray lying inside a closed object.
+#[derive(Clone, Copy)]
+pub struct Span {
+    pub enter: Crossing,
+    pub exit: Crossing,
+}
+
+/// Closed object that can report every interval a ray spends inside it.
+pub trait Solid: Hitable {
+    /// Sorted, non overlapping spans along the whole line of the ray,
+    /// including the ones behind its origin.
+    fn spans(&self, r: &Ray) -> Vec<Span>;
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOp::Union => in_left || in_right,
+            CsgOp::Intersection => in_left && in_right,
+            CsgOp::Difference => in_left && !in_right,
+        }
+    }
+}
+
+pub struct Csg {
+    op: CsgOp,
+    left: Box<dyn Solid + Send + Sync>,
+    right: Box<dyn Solid + Send + Sync>,
+}
+
+impl Csg {
+    pub fn new(
+        op: CsgOp,
+        left: Box<dyn Solid + Send + Sync>,
+        right: Box<dyn Solid + Send + Sync>,
+    ) -> Csg {
+        Csg { op, left, right }
+    }
+
+    pub fn union(left: Box<dyn Solid + Send + Sync>, right: Box<dyn Solid + Send + Sync>) -> Csg {
+        Csg::new(CsgOp::Union, left, right)
+    }
+
+    pub fn intersection(
+        left: Box<dyn Solid + Send + Sync>,
+        right: Box<dyn Solid + Send + Sync>,
+    ) -> Csg {
+        Csg::new(CsgOp::Intersection, left, right)
+    }
+
+    pub fn difference(
+        left: Box<dyn Solid + Send + Sync>,
+        right: Box<dyn Solid + Send + Sync>,
+    ) -> Csg {
+        Csg::new(CsgOp::Difference, left, right)
+    }
+}
+
+impl Solid for Csg {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        // (crossing, belongs to left operand, is an entry)
+        let mut events: Vec<(Crossing, bool, bool)> = Vec::new();
+        for span in self.left.spans(r) {
+            events.push((span.enter, true, true));
+            events.push((span.exit, true, false));
+        }
+        for span in self.right.spans(r) {
+            events.push((span.enter, false, true));
+            events.push((span.exit, false, false));
+        }
+        events.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+        let mut result = Vec::new();
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut inside = false;
+        let mut enter: Option<Crossing> = None;
+
+        for (crossing, is_left, is_entry) in events {
+            if is_left {
+                in_left = is_entry;
+            } else {
+                in_right = is_entry;
+            }
+
+            let now_inside = self.op.inside(in_left, in_right);
+            if now_inside == inside {
+                continue;
+            }
+            inside = now_inside;
+
+            // The subtracted object bounds the result with its inner side.
+            let mut boundary = crossing;
+            if self.op == CsgOp::Difference && !is_left {
+                boundary.normal = -boundary.normal;
+            }
+
+            if inside {
+                enter = Some(boundary);
+            } else if let Some(enter) = enter.take() {
+                result.push(Span {
+                    enter,
+                    exit: boundary,
+                });
+            }
+        }
+
+        result
+    }
+}
+
+impl Hitable for Csg {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.spans(r)
+            .iter()
+            .flat_map(|span| vec![span.enter, span.exit])
+            .find(|crossing| crossing.t > t_min && crossing.t < t_max)
+            .map(|crossing| {
+                HitRecord::new(
+                    crossing.t,
+                    r.point_at(crossing.t),
+                    crossing.normal,
+                    r.direction(),
+                    crossing.material,
+                )
+            })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let left = self.left.bounding_box();
+        match self.op {
+            // The result never reaches out of what it is cut from.
+            CsgOp::Difference => left,
+            _ => match (left, self.right.bounding_box()) {
+                (Some(left), Some(right)) => Some(left.union(&right)),
+                (left, right) => left.or(right),
+            },
+        }
+    }
+}
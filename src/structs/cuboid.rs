@@ -0,0 +1,80 @@
+use crate::stats;
+use crate::structs::aabb::Aabb;
+use crate::structs::csg::{Crossing, Solid, Span};
+use crate::structs::hitable::{HitRecord, Hitable};
+use crate::structs::material::Material;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+/// Axis aligned box.
+pub struct Cuboid {
+    bounds: Aabb,
+    material: Material,
+}
+
+impl Cuboid {
+    pub fn new(corner_a: Point3, corner_b: Point3, material: Material) -> Cuboid {
+        Cuboid {
+            bounds: Aabb::new(corner_a, corner_b),
+            material,
+        }
+    }
+
+    fn normal_at(&self, point: Point3) -> Vec3 {
+        let half = self.bounds.half_size();
+        let local = point - self.bounds.center();
+        let d = [local.x_ / half.x_, local.y_ / half.y_, local.z_ / half.z_];
+
+        if d[0].abs() >= d[1].abs() && d[0].abs() >= d[2].abs() {
+            Vec3::new(d[0].signum(), 0., 0.)
+        } else if d[1].abs() >= d[2].abs() {
+            Vec3::new(0., d[1].signum(), 0.)
+        } else {
+            Vec3::new(0., 0., d[2].signum())
+        }
+    }
+
+    fn crossing(&self, r: &Ray, t: f64) -> Crossing {
+        Crossing {
+            t,
+            normal: self.normal_at(r.point_at(t)),
+            material: self.material,
+        }
+    }
+}
+
+impl Solid for Cuboid {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        stats::record(|stats| stats.primitive_tests += 1);
+        match self.bounds.intersect(r) {
+            Some((t_enter, t_exit)) => vec![Span {
+                enter: self.crossing(r, t_enter),
+                exit: self.crossing(r, t_exit),
+            }],
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Hitable for Cuboid {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::record(|stats| stats.primitive_tests += 1);
+        let (t_enter, t_exit) = self.bounds.intersect(r)?;
+        let t = if t_enter > t_min { t_enter } else { t_exit };
+        if t > t_min && t < t_max {
+            Some(HitRecord::new(
+                t,
+                r.point_at(t),
+                self.normal_at(r.point_at(t)),
+                r.direction(),
+                self.material,
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
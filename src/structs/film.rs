@@ -0,0 +1,560 @@
+use crate::output::tonemap::luminance;
+use crate::structs::filter::Filter;
+use crate::structs::vec3::{Point3, Vec3};
+
+use std::ops::{Add, Mul};
+
+/// Linear radiance gathered for a single pixel.
+#[derive(Debug, Copy, Clone)]
+pub struct FilmPixel {
+    /// Filter weighted sum of the samples splatted into the pixel.
+    pub radiance_sum: Vec3,
+    pub weight_sum: f64,
+    /// Samples taken inside the pixel itself.
+    pub samples: u32,
+    /// Sum and sum of squares of the luminance of those samples.
+    pub luminance_sum: f64,
+    pub luminance_sq_sum: f64,
+}
+
+impl FilmPixel {
+    pub fn new() -> FilmPixel {
+        FilmPixel {
+            radiance_sum: Vec3::zero(),
+            weight_sum: 0.,
+            samples: 0,
+            luminance_sum: 0.,
+            luminance_sq_sum: 0.,
+        }
+    }
+
+    /// Adds a sample taken inside the pixel with unit weight.
+    pub fn add_sample(&mut self, radiance: Vec3) {
+        self.splat(radiance, 1.);
+        self.count_sample(radiance);
+    }
+
+    // Records a sample taken inside the pixel, without splatting it.
+    fn count_sample(&mut self, radiance: Vec3) {
+        let luminance = luminance(&radiance);
+        self.samples += 1;
+        self.luminance_sum += luminance;
+        self.luminance_sq_sum += luminance * luminance;
+    }
+
+    pub fn splat(&mut self, radiance: Vec3, weight: f64) {
+        self.radiance_sum = self.radiance_sum + weight * radiance;
+        self.weight_sum += weight;
+    }
+
+    /// Weighted average radiance of the samples taken so far.
+    pub fn color(&self) -> Vec3 {
+        if self.weight_sum.abs() < 1e-12 {
+            Vec3::zero()
+        } else {
+            self.radiance_sum / self.weight_sum
+        }
+    }
+
+    /// Standard error of the mean luminance divided by its square root,
+    /// which roughly follows how visible the noise is after the display
+    /// gamma. Infinite below two samples.
+    pub fn relative_error(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.samples as f64;
+        let mean = self.luminance_sum / n;
+        let variance = ((self.luminance_sq_sum / n - mean * mean) * n / (n - 1.)).max(0.);
+        (variance / n).sqrt() / mean.max(1e-4).sqrt()
+    }
+
+    pub fn merge(&mut self, other: &FilmPixel) {
+        self.radiance_sum = self.radiance_sum + other.radiance_sum;
+        self.weight_sum += other.weight_sum;
+        self.samples += other.samples;
+        self.luminance_sum += other.luminance_sum;
+        self.luminance_sq_sum += other.luminance_sq_sum;
+    }
+}
+
+impl Default for FilmPixel {
+    fn default() -> FilmPixel {
+        FilmPixel::new()
+    }
+}
+
+/// Kind of scattering at the first hit, lighting is split by it in the AOVs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Lobe {
+    Diffuse,
+    Glossy,
+    Transmission,
+}
+
+impl Lobe {
+    pub const ALL: [Lobe; 3] = [Lobe::Diffuse, Lobe::Glossy, Lobe::Transmission];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lobe::Diffuse => "diffuse",
+            Lobe::Glossy => "glossy",
+            Lobe::Transmission => "transmission",
+        }
+    }
+
+    /// Position in `ALL`.
+    pub fn index(self) -> usize {
+        match self {
+            Lobe::Diffuse => 0,
+            Lobe::Glossy => 1,
+            Lobe::Transmission => 2,
+        }
+    }
+}
+
+/// Light a camera sample found, split by how it reached the first hit for
+/// the lighting AOVs.
+#[derive(Debug, Copy, Clone)]
+pub struct Radiance {
+    /// Emitted at the first hit or reaching it straight from a light or
+    /// the sky, and the background where the camera ray left the scene.
+    pub direct: Vec3,
+    /// Reaching the first hit over other surfaces.
+    pub indirect: Vec3,
+    /// Lobe of the first hit, `None` for the background.
+    pub lobe: Option<Lobe>,
+}
+
+impl Radiance {
+    pub fn zero() -> Radiance {
+        Radiance {
+            direct: Vec3::zero(),
+            indirect: Vec3::zero(),
+            lobe: None,
+        }
+    }
+
+    /// Light that is all direct, like the background.
+    pub fn direct(light: Vec3, lobe: Option<Lobe>) -> Radiance {
+        Radiance {
+            direct: light,
+            indirect: Vec3::zero(),
+            lobe,
+        }
+    }
+
+    pub fn total(&self) -> Vec3 {
+        self.direct + self.indirect
+    }
+
+    /// Adds light that scattered off `scatters` surfaces on its way to the
+    /// camera, up to one of them is direct light.
+    pub fn add(&mut self, scatters: u32, light: Vec3) {
+        if scatters <= 1 {
+            self.direct = self.direct + light;
+        } else {
+            self.indirect = self.indirect + light;
+        }
+    }
+}
+
+/// Keeps the lobe of the left side unless it has none.
+impl Add for Radiance {
+    type Output = Radiance;
+
+    fn add(self, other: Radiance) -> Radiance {
+        Radiance {
+            direct: self.direct + other.direct,
+            indirect: self.indirect + other.indirect,
+            lobe: self.lobe.or(other.lobe),
+        }
+    }
+}
+
+impl Mul<Radiance> for Vec3 {
+    type Output = Radiance;
+
+    fn mul(self, radiance: Radiance) -> Radiance {
+        Radiance {
+            direct: self * radiance.direct,
+            indirect: self * radiance.indirect,
+            lobe: radiance.lobe,
+        }
+    }
+}
+
+impl Mul<Radiance> for f64 {
+    type Output = Radiance;
+
+    fn mul(self, radiance: Radiance) -> Radiance {
+        Radiance {
+            direct: self * radiance.direct,
+            indirect: self * radiance.indirect,
+            lobe: radiance.lobe,
+        }
+    }
+}
+
+/// What a single camera sample saw at its first hit.
+pub struct AovSample {
+    pub albedo: Vec3,
+    pub normal: Vec3,
+    pub position: Point3,
+    pub depth: f64,
+    pub motion: (f64, f64),
+    pub material_id: u32,
+    pub object_id: u32,
+}
+
+/// Arbitrary output variables of a pixel, summed over the samples that hit something.
+#[derive(Debug, Copy, Clone)]
+pub struct AovPixel {
+    pub albedo: Vec3,
+    pub normal: Vec3,
+    pub position: Vec3,
+    pub depth: f64,
+    pub motion: (f64, f64),
+    pub hits: u32,
+    /// Ids of the first sample that hit, 0 for the background.
+    pub material_id: u32,
+    pub object_id: u32,
+    /// Direct and indirect lighting for each lobe, summed with the filter
+    /// weights of the radiance of the pixel.
+    pub lobes: [(Vec3, Vec3); 3],
+    /// The same for light traced onto the pixel, summed over all the
+    /// samples of the film like `Film::light`.
+    pub light_lobes: [(Vec3, Vec3); 3],
+}
+
+impl AovPixel {
+    pub fn new() -> AovPixel {
+        AovPixel {
+            albedo: Vec3::zero(),
+            normal: Vec3::zero(),
+            position: Vec3::zero(),
+            depth: 0.,
+            motion: (0., 0.),
+            hits: 0,
+            material_id: 0,
+            object_id: 0,
+            lobes: [(Vec3::zero(), Vec3::zero()); 3],
+            light_lobes: [(Vec3::zero(), Vec3::zero()); 3],
+        }
+    }
+
+    pub fn add_sample(&mut self, sample: &AovSample) {
+        if self.hits == 0 {
+            self.material_id = sample.material_id;
+            self.object_id = sample.object_id;
+        }
+        self.albedo = self.albedo + sample.albedo;
+        self.normal = self.normal + sample.normal;
+        self.position = self.position + sample.position;
+        self.depth += sample.depth;
+        self.motion = (
+            self.motion.0 + sample.motion.0,
+            self.motion.1 + sample.motion.1,
+        );
+        self.hits += 1;
+    }
+
+    // Adds the lighting of `radiance` to its lobe, weighted by `weight`.
+    fn add_lighting(lobes: &mut [(Vec3, Vec3); 3], radiance: &Radiance, weight: f64) {
+        if let Some(lobe) = radiance.lobe {
+            let lobe = &mut lobes[lobe.index()];
+            *lobe = (
+                lobe.0 + weight * radiance.direct,
+                lobe.1 + weight * radiance.indirect,
+            );
+        }
+    }
+
+    pub fn merge(&mut self, other: &AovPixel) {
+        if self.hits == 0 {
+            self.material_id = other.material_id;
+            self.object_id = other.object_id;
+        }
+        self.albedo = self.albedo + other.albedo;
+        self.normal = self.normal + other.normal;
+        self.position = self.position + other.position;
+        self.depth += other.depth;
+        self.motion = (
+            self.motion.0 + other.motion.0,
+            self.motion.1 + other.motion.1,
+        );
+        self.hits += other.hits;
+        for (lobe, other) in self.lobes.iter_mut().zip(other.lobes.iter()) {
+            *lobe = (lobe.0 + other.0, lobe.1 + other.1);
+        }
+        for (lobe, other) in self.light_lobes.iter_mut().zip(other.light_lobes.iter()) {
+            *lobe = (lobe.0 + other.0, lobe.1 + other.1);
+        }
+    }
+}
+
+impl Default for AovPixel {
+    fn default() -> AovPixel {
+        AovPixel::new()
+    }
+}
+
+/// Rectangular part of a film, rendered on its own and merged back.
+///
+/// Samples are taken for the pixels from `(x0, y0)` on, but the tile also
+/// stores a border of pixels around them the filter splats into.
+pub struct FilmTile {
+    pub x0: u32,
+    pub y0: u32,
+    pub width: u32,
+    pub height: u32,
+    // Stored region, clipped to the film.
+    store_x0: u32,
+    store_y0: u32,
+    store_width: u32,
+    store_height: u32,
+    pixels: Vec<FilmPixel>,
+    aovs: Option<Vec<AovPixel>>,
+    filter: Filter,
+    // Light traced to pixels anywhere on the film.
+    light: Vec<((u32, u32), Radiance)>,
+}
+
+impl FilmTile {
+    fn index(&self, x: u32, y: u32) -> usize {
+        ((y - self.store_y0) * self.store_width + x - self.store_x0) as usize
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> &mut FilmPixel {
+        let index = self.index(x, y);
+        &mut self.pixels[index]
+    }
+
+    pub fn aov_mut(&mut self, x: u32, y: u32) -> Option<&mut AovPixel> {
+        let index = self.index(x, y);
+        self.aovs.as_mut().map(|aovs| &mut aovs[index])
+    }
+
+    /// Splats a sample taken in pixel `(x, y)` at the film position
+    /// `(fx, fy)`, measured in pixels from the top left corner. Its
+    /// lighting goes to the AOVs with the same weights.
+    pub fn add_sample(&mut self, (x, y): (u32, u32), (fx, fy): (f64, f64), radiance: &Radiance) {
+        let total = radiance.total();
+        self.pixel_mut(x, y).count_sample(total);
+
+        let radius = self.filter.radius();
+        let x_first = ((fx - 0.5 - radius).ceil().max(self.store_x0 as f64)) as u32;
+        let y_first = ((fy - 0.5 - radius).ceil().max(self.store_y0 as f64)) as u32;
+        let x_last = (fx - 0.5 + radius)
+            .floor()
+            .min((self.store_x0 + self.store_width - 1) as f64);
+        let y_last = (fy - 0.5 + radius)
+            .floor()
+            .min((self.store_y0 + self.store_height - 1) as f64);
+        if x_last < 0. || y_last < 0. {
+            return;
+        }
+
+        for py in y_first..=y_last as u32 {
+            for px in x_first..=x_last as u32 {
+                let weight = self
+                    .filter
+                    .evaluate(fx - px as f64 - 0.5, fy - py as f64 - 0.5);
+                if weight != 0. {
+                    self.pixel_mut(px, py).splat(total, weight);
+                    let index = self.index(px, py);
+                    if let Some(aovs) = self.aovs.as_mut() {
+                        AovPixel::add_lighting(&mut aovs[index].lobes, radiance, weight);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds light that was traced from a light to pixel `(x, y)`, which
+    /// may lie outside of the tile.
+    pub fn add_light(&mut self, (x, y): (u32, u32), radiance: Radiance) {
+        self.light.push(((x, y), radiance));
+    }
+}
+
+/// HDR image that samples accumulate into. Rows are stored top to bottom.
+pub struct Film {
+    width: u32,
+    height: u32,
+    pixels: Vec<FilmPixel>,
+    aovs: Option<Vec<AovPixel>>,
+    filter: Filter,
+    // Radiance light tracing deposited on every pixel, summed over all the
+    // samples of the film rather than the pixel's own.
+    light: Vec<Vec3>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Film {
+        Film {
+            width,
+            height,
+            pixels: vec![FilmPixel::new(); (width * height) as usize],
+            aovs: None,
+            filter: Filter::default(),
+            light: vec![Vec3::zero(); (width * height) as usize],
+        }
+    }
+
+    /// Also record the AOVs of the first hit.
+    pub fn with_aovs(mut self) -> Film {
+        self.aovs = Some(vec![AovPixel::new(); (self.width * self.height) as usize]);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Film {
+        self.filter = filter;
+        self
+    }
+
+    pub fn filter(&self) -> Filter {
+        self.filter
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> &FilmPixel {
+        &self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> &mut FilmPixel {
+        &mut self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn pixels(&self) -> &[FilmPixel] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [FilmPixel] {
+        &mut self.pixels
+    }
+
+    pub fn aovs(&self) -> Option<&[AovPixel]> {
+        self.aovs.as_deref()
+    }
+
+    pub fn aovs_mut(&mut self) -> Option<&mut [AovPixel]> {
+        self.aovs.as_deref_mut()
+    }
+
+    /// Sums of the light traced onto every pixel, row by row.
+    pub fn light(&self) -> &[Vec3] {
+        &self.light
+    }
+
+    pub fn light_mut(&mut self) -> &mut [Vec3] {
+        &mut self.light
+    }
+
+    /// Factor turning the sums of traced light into radiance. Every sample
+    /// of the film traced one light path, each of them covering the whole
+    /// film.
+    pub fn light_scale(&self) -> f64 {
+        let samples: u64 = self.pixels.iter().map(|pixel| pixel.samples as u64).sum();
+        if samples == 0 {
+            0.
+        } else {
+            self.pixels.len() as f64 / samples as f64
+        }
+    }
+
+    /// Average linear radiance of every pixel, row by row.
+    pub fn colors(&self) -> Vec<Vec3> {
+        let scale = self.light_scale();
+        self.pixels
+            .iter()
+            .zip(self.light.iter())
+            .map(|(pixel, light)| pixel.color() + scale * *light)
+            .collect()
+    }
+
+    /// Replaces the radiance of every pixel keeping the sample counts, as
+    /// done by post-processes.
+    pub fn set_colors(&mut self, colors: &[Vec3]) {
+        assert_eq!(colors.len(), self.pixels.len());
+        for (pixel, col) in self.pixels.iter_mut().zip(colors.iter()) {
+            pixel.radiance_sum = pixel.weight_sum * col;
+        }
+        for light in self.light.iter_mut() {
+            *light = Vec3::zero();
+        }
+    }
+
+    pub fn discard_aovs(&mut self) {
+        self.aovs = None;
+    }
+
+    /// Empty tile covering the given rectangle of the film, plus the
+    /// border the filter reaches.
+    pub fn tile(&self, x0: u32, y0: u32, width: u32, height: u32) -> FilmTile {
+        let pad = (self.filter.radius() - 0.5).ceil().max(0.) as u32;
+        let store_x0 = x0.saturating_sub(pad);
+        let store_y0 = y0.saturating_sub(pad);
+        let store_width = (x0 + width + pad).min(self.width) - store_x0;
+        let store_height = (y0 + height + pad).min(self.height) - store_y0;
+        let size = (store_width * store_height) as usize;
+
+        FilmTile {
+            x0,
+            y0,
+            width,
+            height,
+            store_x0,
+            store_y0,
+            store_width,
+            store_height,
+            pixels: vec![FilmPixel::new(); size],
+            aovs: self.aovs.as_ref().map(|_| vec![AovPixel::new(); size]),
+            filter: self.filter,
+            light: Vec::new(),
+        }
+    }
+
+    pub fn merge_tile(&mut self, tile: &FilmTile) {
+        for y in 0..tile.store_height {
+            let film_row = ((tile.store_y0 + y) * self.width + tile.store_x0) as usize;
+            let tile_row = (y * tile.store_width) as usize;
+            for x in 0..tile.store_width as usize {
+                self.pixels[film_row + x].merge(&tile.pixels[tile_row + x]);
+                if let (Some(aovs), Some(tile_aovs)) = (self.aovs.as_mut(), tile.aovs.as_ref()) {
+                    aovs[film_row + x].merge(&tile_aovs[tile_row + x]);
+                }
+            }
+        }
+        for ((x, y), radiance) in tile.light.iter() {
+            let index = (y * self.width + x) as usize;
+            self.light[index] = self.light[index] + radiance.total();
+            if let Some(aovs) = self.aovs.as_mut() {
+                AovPixel::add_lighting(&mut aovs[index].light_lobes, radiance, 1.);
+            }
+        }
+    }
+
+    /// Adds the samples of another film of the same size.
+    pub fn merge(&mut self, other: &Film) {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        for (pixel, other) in self.pixels.iter_mut().zip(other.pixels.iter()) {
+            pixel.merge(other);
+        }
+        for (light, other) in self.light.iter_mut().zip(other.light.iter()) {
+            *light = *light + *other;
+        }
+        if let (Some(aovs), Some(other)) = (self.aovs.as_mut(), other.aovs.as_ref()) {
+            for (aov, other) in aovs.iter_mut().zip(other.iter()) {
+                aov.merge(other);
+            }
+        }
+    }
+}
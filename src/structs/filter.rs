@@ -0,0 +1,135 @@
+use std::f64::consts::PI;
+
+/// Pixel reconstruction filter, samples are splatted into every pixel whose
+/// center lies within `radius` pixels of them, weighted by the filter.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    Box {
+        radius: f64,
+    },
+    Tent {
+        radius: f64,
+    },
+    Gaussian {
+        radius: f64,
+        alpha: f64,
+    },
+    /// Mitchell-Netravali cubic with the `b` and `c` parameters.
+    Mitchell {
+        radius: f64,
+        b: f64,
+        c: f64,
+    },
+    /// Sinc windowed by a wider sinc of `tau` lobes.
+    Lanczos {
+        radius: f64,
+        tau: f64,
+    },
+    BlackmanHarris {
+        radius: f64,
+    },
+}
+
+impl Default for Filter {
+    /// Plain average of the samples inside a pixel.
+    fn default() -> Filter {
+        Filter::Box { radius: 0.5 }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        1.
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+impl Filter {
+    /// Filter with its usual parameters, `radius` overrides the default one.
+    pub fn from_name(name: &str, radius: Option<f64>) -> Option<Filter> {
+        let filter = match name.to_ascii_lowercase().as_str() {
+            "box" => Filter::Box {
+                radius: radius.unwrap_or(0.5),
+            },
+            "tent" | "triangle" => Filter::Tent {
+                radius: radius.unwrap_or(1.),
+            },
+            "gaussian" => Filter::Gaussian {
+                radius: radius.unwrap_or(1.5),
+                alpha: 2.,
+            },
+            "mitchell" => Filter::Mitchell {
+                radius: radius.unwrap_or(2.),
+                b: 1. / 3.,
+                c: 1. / 3.,
+            },
+            "lanczos" => Filter::Lanczos {
+                radius: radius.unwrap_or(3.),
+                tau: 3.,
+            },
+            "blackman_harris" | "blackman-harris" => Filter::BlackmanHarris {
+                radius: radius.unwrap_or(2.),
+            },
+            _ => return None,
+        };
+        Some(filter)
+    }
+
+    pub fn radius(&self) -> f64 {
+        match *self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius, .. }
+            | Filter::Mitchell { radius, .. }
+            | Filter::Lanczos { radius, .. }
+            | Filter::BlackmanHarris { radius } => radius,
+        }
+    }
+
+    /// Weight of a sample at offset `(dx, dy)` from a pixel center, in pixels.
+    /// The box covers `[-radius, radius)`, so with the default radius a
+    /// sample on the border of two pixels only counts for the one it lies in.
+    pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        if let Filter::Box { radius } = *self {
+            return if -radius <= x && x < radius { 1. } else { 0. };
+        }
+        let x = x.abs();
+        if x > self.radius() {
+            return 0.;
+        }
+
+        match *self {
+            Filter::Box { .. } => 1.,
+            Filter::Tent { radius } => radius - x,
+            Filter::Gaussian { radius, alpha } => {
+                ((-alpha * x * x).exp() - (-alpha * radius * radius).exp()).max(0.)
+            }
+            Filter::Mitchell { radius, b, c } => {
+                let x = 2. * x / radius;
+                if x > 1. {
+                    ((-b - 6. * c) * x * x * x
+                        + (6. * b + 30. * c) * x * x
+                        + (-12. * b - 48. * c) * x
+                        + (8. * b + 24. * c))
+                        / 6.
+                } else {
+                    ((12. - 9. * b - 6. * c) * x * x * x
+                        + (-18. + 12. * b + 6. * c) * x * x
+                        + (6. - 2. * b))
+                        / 6.
+                }
+            }
+            Filter::Lanczos { tau, .. } => sinc(x) * sinc(x / tau),
+            Filter::BlackmanHarris { radius } => {
+                let n = 0.5 + x / (2. * radius);
+                0.35875 - 0.48829 * (2. * PI * n).cos() + 0.14128 * (4. * PI * n).cos()
+                    - 0.01168 * (6. * PI * n).cos()
+            }
+        }
+    }
+}
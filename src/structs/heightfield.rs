@@ -0,0 +1,342 @@
+use crate::stats;
+use crate::structs::aabb::Aabb;
+use crate::structs::hitable::{HitRecord, Hitable};
+use crate::structs::material::Material;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+/// Terrain given by a regular grid of heights over the xz plane.
+///
+/// Rays are intersected by walking a min-max mipmap of the grid: every
+/// level stores the height range of 2x2 blocks of the level below, so whole
+/// regions the ray passes above or below are skipped at once.
+pub struct Heightfield {
+    heights: Vec<f64>,
+    normals: Vec<Vec3>,
+    nx: usize,
+    nz: usize,
+    corner: Point3,
+    cell_x: f64,
+    cell_z: f64,
+    // levels[0] holds one (min, max) pair per cell, the last level a single one.
+    levels: Vec<MinMaxLevel>,
+    material: Material,
+}
+
+struct MinMaxLevel {
+    width: usize,
+    depth: usize,
+    ranges: Vec<(f64, f64)>,
+}
+
+impl Heightfield {
+    /// `heights` has `nx * nz` samples, row by row along x. The grid spans
+    /// `size.x_` by `size.z_` starting at `corner`, a sample of 1 is raised
+    /// by `size.y_` above `corner.y_`.
+    pub fn new(
+        heights: &[f64],
+        nx: usize,
+        nz: usize,
+        corner: Point3,
+        size: Vec3,
+        material: Material,
+    ) -> Heightfield {
+        assert!(nx >= 2 && nz >= 2, "heightfield needs at least 2x2 samples");
+        assert_eq!(heights.len(), nx * nz);
+
+        let heights: Vec<f64> = heights.iter().map(|h| corner.y_ + h * size.y_).collect();
+        let cell_x = size.x_ / (nx - 1) as f64;
+        let cell_z = size.z_ / (nz - 1) as f64;
+
+        let mut field = Heightfield {
+            heights,
+            normals: Vec::new(),
+            nx,
+            nz,
+            corner,
+            cell_x,
+            cell_z,
+            levels: Vec::new(),
+            material,
+        };
+        field.normals = field.vertex_normals();
+        field.levels = field.min_max_levels();
+        field
+    }
+
+    /// Heights are sampled from `f(u, v)` with `u` and `v` in `[0, 1]`.
+    pub fn from_fn<F: Fn(f64, f64) -> f64>(
+        f: F,
+        nx: usize,
+        nz: usize,
+        corner: Point3,
+        size: Vec3,
+        material: Material,
+    ) -> Heightfield {
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                heights.push(f(i as f64 / (nx - 1) as f64, j as f64 / (nz - 1) as f64));
+            }
+        }
+        Heightfield::new(&heights, nx, nz, corner, size, material)
+    }
+
+    /// Image rows run along z, black maps to `corner.y_`, white to `corner.y_ + size.y_`.
+    pub fn from_image(
+        img: &image::DynamicImage,
+        corner: Point3,
+        size: Vec3,
+        material: Material,
+    ) -> Heightfield {
+        let gray = img.to_luma16();
+        let heights: Vec<f64> = gray
+            .pixels()
+            .map(|p| p[0] as f64 / u16::MAX as f64)
+            .collect();
+        Heightfield::new(
+            &heights,
+            gray.width() as usize,
+            gray.height() as usize,
+            corner,
+            size,
+            material,
+        )
+    }
+
+    /// Terrain height below the point `(x, z)`, clamped to the grid edges.
+    pub fn height_at(&self, x: f64, z: f64) -> f64 {
+        let fx = num::clamp((x - self.corner.x_) / self.cell_x, 0., (self.nx - 1) as f64);
+        let fz = num::clamp((z - self.corner.z_) / self.cell_z, 0., (self.nz - 1) as f64);
+        let i = (fx as usize).min(self.nx - 2);
+        let j = (fz as usize).min(self.nz - 2);
+        let (dx, dz) = (fx - i as f64, fz - j as f64);
+
+        // Interpolate over the same triangles the intersection uses.
+        let h00 = self.height(i, j);
+        let h11 = self.height(i + 1, j + 1);
+        if dx >= dz {
+            h00 + dx * (self.height(i + 1, j) - h00) + dz * (h11 - self.height(i + 1, j))
+        } else {
+            h00 + dz * (self.height(i, j + 1) - h00) + dx * (h11 - self.height(i, j + 1))
+        }
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.nx + i]
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        Point3::new(
+            self.corner.x_ + i as f64 * self.cell_x,
+            self.height(i, j),
+            self.corner.z_ + j as f64 * self.cell_z,
+        )
+    }
+
+    fn vertex_normals(&self) -> Vec<Vec3> {
+        let mut normals = Vec::with_capacity(self.nx * self.nz);
+        for j in 0..self.nz {
+            for i in 0..self.nx {
+                let (il, ir) = (i.saturating_sub(1), (i + 1).min(self.nx - 1));
+                let (jl, jr) = (j.saturating_sub(1), (j + 1).min(self.nz - 1));
+                let dhdx =
+                    (self.height(ir, j) - self.height(il, j)) / ((ir - il) as f64 * self.cell_x);
+                let dhdz =
+                    (self.height(i, jr) - self.height(i, jl)) / ((jr - jl) as f64 * self.cell_z);
+                normals.push(Vec3::unit_vector(Vec3::new(-dhdx, 1., -dhdz)));
+            }
+        }
+        normals
+    }
+
+    fn min_max_levels(&self) -> Vec<MinMaxLevel> {
+        let (width, depth) = (self.nx - 1, self.nz - 1);
+        let mut ranges = Vec::with_capacity(width * depth);
+        for j in 0..depth {
+            for i in 0..width {
+                let corners = [
+                    self.height(i, j),
+                    self.height(i + 1, j),
+                    self.height(i, j + 1),
+                    self.height(i + 1, j + 1),
+                ];
+                let min = corners.iter().cloned().fold(f64::INFINITY, f64::min);
+                let max = corners.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                ranges.push((min, max));
+            }
+        }
+
+        let mut levels = vec![MinMaxLevel {
+            width,
+            depth,
+            ranges,
+        }];
+        while levels.last().is_some_and(|l| l.width > 1 || l.depth > 1) {
+            let below = levels.last().unwrap();
+            let width = below.width.div_ceil(2);
+            let depth = below.depth.div_ceil(2);
+            let mut ranges = Vec::with_capacity(width * depth);
+            for j in 0..depth {
+                for i in 0..width {
+                    let mut range = (f64::INFINITY, f64::NEG_INFINITY);
+                    for (ci, cj) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+                        let (bi, bj) = (2 * i + ci, 2 * j + cj);
+                        if bi < below.width && bj < below.depth {
+                            let (lo, hi) = below.ranges[bj * below.width + bi];
+                            range = (range.0.min(lo), range.1.max(hi));
+                        }
+                    }
+                    ranges.push(range);
+                }
+            }
+            levels.push(MinMaxLevel {
+                width,
+                depth,
+                ranges,
+            });
+        }
+
+        levels
+    }
+
+    fn node_bounds(&self, level: usize, i: usize, j: usize) -> Aabb {
+        let cells = 1 << level;
+        let (lo, hi) = self.levels[level].ranges[j * self.levels[level].width + i];
+        let x0 = self.corner.x_ + (i * cells) as f64 * self.cell_x;
+        let z0 = self.corner.z_ + (j * cells) as f64 * self.cell_z;
+        let x1 = self.corner.x_ + ((i + 1) * cells).min(self.nx - 1) as f64 * self.cell_x;
+        let z1 = self.corner.z_ + ((j + 1) * cells).min(self.nz - 1) as f64 * self.cell_z;
+        // Padded so flat regions and shared edges survive the slab test.
+        let pad = 1e-9 * (1. + self.cell_x.max(self.cell_z));
+        Aabb::new(
+            Point3::new(x0 - pad, lo - pad, z0 - pad),
+            Point3::new(x1 + pad, hi + pad, z1 + pad),
+        )
+    }
+
+    fn hit_node(
+        &self,
+        r: &Ray,
+        level: usize,
+        i: usize,
+        j: usize,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord> {
+        stats::record(|stats| stats.heightfield_nodes += 1);
+        if level == 0 {
+            return self.hit_cell(r, i, j, t_min, t_max);
+        }
+
+        let below = &self.levels[level - 1];
+        let mut children = [(0., 0, 0); 4];
+        let mut count = 0;
+        for (ci, cj) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+            let (bi, bj) = (2 * i + ci, 2 * j + cj);
+            if bi >= below.width || bj >= below.depth {
+                continue;
+            }
+            if let Some((t_enter, t_exit)) = self.node_bounds(level - 1, bi, bj).intersect(r) {
+                if t_enter < t_max && t_exit > t_min {
+                    children[count] = (t_enter, bi, bj);
+                    count += 1;
+                }
+            }
+        }
+        let children = &mut children[..count];
+        children.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut closest_so_far = t_max;
+        let mut last_hit = None;
+        for &(t_enter, bi, bj) in children.iter() {
+            if t_enter >= closest_so_far {
+                break;
+            }
+            if let Some(hit) = self.hit_node(r, level - 1, bi, bj, t_min, closest_so_far) {
+                closest_so_far = hit.t;
+                last_hit = Some(hit);
+            }
+        }
+
+        last_hit
+    }
+
+    fn hit_cell(&self, r: &Ray, i: usize, j: usize, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let v00 = (i, j);
+        let v11 = (i + 1, j + 1);
+        let mut closest_so_far = t_max;
+        let mut last_hit = None;
+
+        for third in [(i + 1, j), (i, j + 1)].iter() {
+            let triangle = [v00, *third, v11];
+            if let Some(hit) = self.hit_triangle(r, &triangle, t_min, closest_so_far) {
+                closest_so_far = hit.t;
+                last_hit = Some(hit);
+            }
+        }
+
+        last_hit
+    }
+
+    // Moller-Trumbore with normals interpolated from the vertices.
+    fn hit_triangle(
+        &self,
+        r: &Ray,
+        triangle: &[(usize, usize); 3],
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord> {
+        stats::record(|stats| stats.primitive_tests += 1);
+        let [a, b, c] = *triangle;
+        let p0 = self.vertex(a.0, a.1);
+        let e1 = self.vertex(b.0, b.1) - p0;
+        let e2 = self.vertex(c.0, c.1) - p0;
+
+        let pvec = Vec3::cross(&r.direction(), &e2);
+        let det = Vec3::dot(&e1, &pvec);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let inv_det = 1. / det;
+        let tvec = r.origin() - p0;
+        let b1 = Vec3::dot(&tvec, &pvec) * inv_det;
+        if !(0. ..=1.).contains(&b1) {
+            return None;
+        }
+        let qvec = Vec3::cross(&tvec, &e1);
+        let b2 = Vec3::dot(&r.direction(), &qvec) * inv_det;
+        if b2 < 0. || b1 + b2 > 1. {
+            return None;
+        }
+        let t = Vec3::dot(&e2, &qvec) * inv_det;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        let normal_at = |(i, j): (usize, usize)| self.normals[j * self.nx + i];
+        let normal = Vec3::unit_vector(
+            (1. - b1 - b2) * normal_at(a) + b1 * normal_at(b) + b2 * normal_at(c),
+        );
+        let hit_point = r.point_at(t);
+        let u = (hit_point.x_ - self.corner.x_) / (self.cell_x * (self.nx - 1) as f64);
+        let v = (hit_point.z_ - self.corner.z_) / (self.cell_z * (self.nz - 1) as f64);
+
+        Some(HitRecord::new(t, hit_point, normal, r.direction(), self.material).with_uv(u, v))
+    }
+}
+
+impl Hitable for Heightfield {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let top = self.levels.len() - 1;
+        let (t_enter, t_exit) = self.node_bounds(top, 0, 0).intersect(r)?;
+        if t_enter >= t_max || t_exit <= t_min {
+            return None;
+        }
+        self.hit_node(r, top, 0, 0, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.node_bounds(self.levels.len() - 1, 0, 0))
+    }
+}
@@ -1,9 +1,15 @@
+use crate::structs::aabb::Aabb;
+use crate::structs::light::{Environment, SphereLight};
 use crate::structs::material::Material;
 use crate::structs::ray::Ray;
+use crate::structs::sphere::Sphere;
 use crate::structs::vec3::{Point3, Vec3};
 
 pub trait Hitable {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+
+    /// Box the object lies in, `None` when it holds nothing.
+    fn bounding_box(&self) -> Option<Aabb>;
 }
 
 pub struct HitRecord {
@@ -12,6 +18,9 @@ pub struct HitRecord {
     pub hit_point: Point3,
     pub out_normal: Vec3,
     pub material: Material,
+    pub uv: (f64, f64),
+    /// One based index in the top level `HitList`, 0 when not set.
+    pub object_id: u32,
 }
 
 impl HitRecord {
@@ -30,12 +39,22 @@ impl HitRecord {
             hit_point,
             out_normal,
             material,
+            uv: (0., 0.),
+            object_id: 0,
         }
     }
+
+    pub fn with_uv(mut self, u: f64, v: f64) -> HitRecord {
+        self.uv = (u, v);
+        self
+    }
 }
 
 pub struct HitList {
     pub elements: Vec<Box<dyn Hitable + Send + Sync>>,
+    /// Emissive spheres among the elements, added with `push_light`.
+    pub lights: Vec<SphereLight>,
+    pub environment: Environment,
 }
 
 impl Hitable for HitList {
@@ -43,31 +62,66 @@ impl Hitable for HitList {
         let mut closest_so_far = t_max;
         let mut last_hit: Option<HitRecord> = None;
 
-        for item in &self.elements {
-            if let Some(hit) = item.hit(r, t_min, closest_so_far) {
+        for (index, item) in self.elements.iter().enumerate() {
+            if let Some(mut hit) = item.hit(r, t_min, closest_so_far) {
                 closest_so_far = hit.t;
+                hit.object_id = index as u32 + 1;
                 last_hit = Some(hit);
             }
         }
 
         last_hit
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.elements
+            .iter()
+            .filter_map(|element| element.bounding_box())
+            .reduce(|a, b| a.union(&b))
+    }
 }
 
 impl HitList {
-    pub fn with_capacity(capacity: usize) -> HitList {
+    /// Scene of `elements` under the sky.
+    pub fn new(elements: Vec<Box<dyn Hitable + Send + Sync>>) -> HitList {
         HitList {
-            elements: Vec::with_capacity(capacity),
+            elements,
+            lights: Vec::new(),
+            environment: Environment::default(),
         }
     }
 
+    pub fn with_capacity(capacity: usize) -> HitList {
+        HitList::new(Vec::with_capacity(capacity))
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> HitList {
+        self.environment = environment;
+        self
+    }
+
     pub fn push(&mut self, item: Box<dyn Hitable + Send + Sync>) {
         self.elements.push(item);
     }
 
+    /// Adds a glowing sphere that integrators sample as a light source.
+    pub fn push_light(&mut self, center: Point3, radius: f64, emission: Vec3) {
+        self.push(Box::new(Sphere::new(
+            radius,
+            center,
+            Material::new_diffuse_light(emission),
+        )));
+        self.lights.push(SphereLight {
+            center,
+            radius,
+            emission,
+            object_id: self.elements.len() as u32,
+        });
+    }
+
+    /// Moves the elements from `at` on to a new list. Lights stay, their
+    /// ids refer to this list.
     pub fn split_off(&mut self, at: usize) -> Self {
-        HitList {
-            elements: self.elements.split_off(at),
-        }
+        HitList::new(self.elements.split_off(at)).with_environment(self.environment)
     }
 }
@@ -0,0 +1,108 @@
+use crate::sampler::warp;
+use crate::structs::vec3::{Point3, Vec3};
+
+use std::f64::consts::PI;
+
+/// Light arriving from far away, along rays that leave the scene.
+#[derive(Debug, Copy, Clone, Default)]
+pub enum Environment {
+    /// White at the horizon fading to light blue overhead.
+    #[default]
+    Sky,
+    /// The same radiance from every direction.
+    Constant(Vec3),
+}
+
+impl Environment {
+    pub fn radiance(&self, direction: &Vec3) -> Vec3 {
+        match self {
+            Environment::Sky => {
+                let t = 0.5 * (Vec3::unit_vector(*direction).y_ + 1.);
+                (1. - t) * Vec3::new(1., 1., 1.) + t * Vec3::new(0.5, 0.7, 1.0)
+            }
+            Environment::Constant(radiance) => *radiance,
+        }
+    }
+
+    pub fn is_black(&self) -> bool {
+        match self {
+            Environment::Sky => false,
+            Environment::Constant(radiance) => {
+                radiance.x_ <= 0. && radiance.y_ <= 0. && radiance.z_ <= 0.
+            }
+        }
+    }
+}
+
+/// Emissive sphere known to the scene, so integrators can aim rays at it.
+#[derive(Debug, Copy, Clone)]
+pub struct SphereLight {
+    pub center: Point3,
+    pub radius: f64,
+    pub emission: Vec3,
+    /// `object_id` of the sphere in the scene.
+    pub object_id: u32,
+}
+
+/// Direction towards a light found by light sampling.
+#[derive(Debug, Copy, Clone)]
+pub struct LightSample {
+    pub direction: Vec3,
+    /// Up to the light, infinite for the environment.
+    pub distance: f64,
+    pub radiance: Vec3,
+    /// Density in solid angle.
+    pub pdf: f64,
+}
+
+impl SphereLight {
+    // Cosine of the half angle of the cone the sphere fills seen from `p`,
+    // `None` inside the sphere.
+    fn cos_max(&self, p: &Point3) -> Option<f64> {
+        let to_center = self.center - p;
+        let distance_sq = Vec3::dot(&to_center, &to_center);
+        let radius_sq = self.radius * self.radius;
+        if distance_sq <= radius_sq {
+            None
+        } else {
+            Some((1. - radius_sq / distance_sq).max(0.).sqrt())
+        }
+    }
+
+    /// Direction from `p` to a point on the visible side of the sphere,
+    /// uniform over the cone it fills. `None` from inside the sphere.
+    pub fn sample(&self, p: &Point3, u: (f64, f64)) -> Option<LightSample> {
+        let cos_max = self.cos_max(p)?;
+        let axis = Vec3::unit_vector(self.center - p);
+        let direction = Vec3::unit_vector(warp::uniform_cone(&axis, cos_max, u));
+
+        // Nearest crossing of the sphere, the closest approach when the
+        // direction only grazes it.
+        let oc = p - self.center;
+        let b = Vec3::dot(&oc, &direction);
+        let c = Vec3::dot(&oc, &oc) - self.radius * self.radius;
+        let distance = -b - (b * b - c).max(0.).sqrt();
+
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.emission,
+            pdf: 1. / (2. * PI * (1. - cos_max)),
+        })
+    }
+
+    /// Density of `sample` picking `direction` from `p`.
+    pub fn pdf(&self, p: &Point3, direction: &Vec3) -> f64 {
+        match self.cos_max(p) {
+            Some(cos_max)
+                if Vec3::dot(
+                    &Vec3::unit_vector(self.center - p),
+                    &Vec3::unit_vector(*direction),
+                ) >= cos_max =>
+            {
+                1. / (2. * PI * (1. - cos_max))
+            }
+            _ => 0.,
+        }
+    }
+}
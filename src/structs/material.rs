@@ -1,14 +1,17 @@
+use crate::sampler::{warp, Sampler};
+use crate::structs::film::Lobe;
 use crate::structs::hitable::HitRecord;
 use crate::structs::ray::Ray;
 use crate::structs::vec3::Vec3;
 
-use rand::Rng;
+use std::f64::consts::PI;
 
 #[derive(Clone, Copy)]
 pub enum Material {
     Lambertian(Lambertian),
     Metal(Metal),
     Dielectric(Dielectric),
+    DiffuseLight(DiffuseLight),
 }
 
 impl Material {
@@ -24,11 +27,85 @@ impl Material {
         Material::Dielectric(Dielectric { refraction })
     }
 
-    pub fn scatter<R: Rng>(&self, r: &Ray, hit_record: &HitRecord, rng: &mut R) -> Option<Ray> {
+    pub fn new_diffuse_light(emission: Vec3) -> Self {
+        Material::DiffuseLight(DiffuseLight { emission })
+    }
+
+    /// Takes the same three sample dimensions whatever the material, so
+    /// every bounce of a path lines up with the same dimensions.
+    ///
+    /// Directions are drawn with density `pdf`, or from a delta distribution
+    /// for specular materials, and the light coming back along them is
+    /// weighted by `attenuation`.
+    pub fn scatter(
+        &self,
+        r: &Ray,
+        hit_record: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<Ray> {
+        let u = sampler.get_1d();
+        let u2 = sampler.get_2d();
+        match self {
+            Material::Lambertian(lamb) => Some(lamb.scatter(r, hit_record, u2)),
+            Material::Metal(met) => met.scatter(r, hit_record, u, u2),
+            Material::Dielectric(diel) => Some(diel.scatter(r, hit_record, u)),
+            Material::DiffuseLight(_) => None,
+        }
+    }
+
+    /// Radiance the surface gives off towards where the ray came from.
+    pub fn emitted(&self, hit_record: &HitRecord) -> Vec3 {
+        match self {
+            Material::DiffuseLight(light) if hit_record.front_face => light.emission,
+            _ => Vec3::zero(),
+        }
+    }
+
+    /// Whether the surface only scatters into a few directions, so light
+    /// sampling cannot find them and `eval` and `pdf` are zero. Fuzzy metal
+    /// counts too, its directions have no density to evaluate.
+    pub fn is_specular(&self) -> bool {
+        matches!(self, Material::Metal(_) | Material::Dielectric(_))
+    }
+
+    /// BSDF times the cosine to the normal for light arriving from
+    /// `direction` and leaving towards where the ray came from.
+    pub fn eval(&self, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        match self {
+            Material::Lambertian(lamb) => {
+                let cos = Vec3::dot(&hit_record.out_normal, &Vec3::unit_vector(*direction));
+                if cos > 0. {
+                    (cos / PI) * lamb.albedo
+                } else {
+                    Vec3::zero()
+                }
+            }
+            _ => Vec3::zero(),
+        }
+    }
+
+    /// Density of `scatter` picking `direction`, per solid angle.
+    pub fn pdf(&self, hit_record: &HitRecord, direction: &Vec3) -> f64 {
         match self {
-            Material::Lambertian(lamb) => Some(lamb.scatter(r, hit_record, rng)),
-            Material::Metal(met) => met.scatter(r, hit_record, rng),
-            Material::Dielectric(diel) => Some(diel.scatter(r, hit_record, rng)),
+            Material::Lambertian(_) => {
+                let cos = Vec3::dot(&hit_record.out_normal, &Vec3::unit_vector(*direction));
+                cos.max(0.) / PI
+            }
+            _ => 0.,
+        }
+    }
+
+    /// Mirrored and refracted rays of a specular surface with the share of
+    /// light each carries, empty for other surfaces. Fuzzy metal reflects
+    /// like a mirror here.
+    pub fn specular_lobes(&self, r: &Ray, hit_record: &HitRecord) -> Vec<(Ray, Vec3)> {
+        match self {
+            Material::Metal(met) => {
+                let direction = reflect(&Vec3::unit_vector(r.direction()), &hit_record.out_normal);
+                vec![(Ray::new(hit_record.hit_point, direction), met.albedo)]
+            }
+            Material::Dielectric(diel) => diel.lobes(r, hit_record),
+            _ => Vec::new(),
         }
     }
 
@@ -37,8 +114,67 @@ impl Material {
             Material::Lambertian(lamb) => lamb.attenuation(),
             Material::Metal(met) => met.attenuation(),
             Material::Dielectric(_) => Vec3::new(1., 1., 1.),
+            Material::DiffuseLight(_) => Vec3::zero(),
+        }
+    }
+
+    /// Extra weight of light traced from a light source when it scatters
+    /// from `hit_record` into `scattered`. Camera paths keep their radiance
+    /// when refracted, for light going the other way to match it has to
+    /// change by the squared ratio of the refractive indices.
+    pub fn light_scale(&self, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        match self {
+            Material::Dielectric(diel)
+                if Vec3::dot(&scattered.direction(), &hit_record.out_normal) < 0. =>
+            {
+                let ratio = if hit_record.front_face {
+                    1. / diel.refraction
+                } else {
+                    diel.refraction
+                };
+                ratio * ratio
+            }
+            _ => 1.,
+        }
+    }
+
+    pub fn lobe(&self) -> Lobe {
+        match self {
+            Material::Lambertian(_) | Material::DiffuseLight(_) => Lobe::Diffuse,
+            Material::Metal(_) => Lobe::Glossy,
+            Material::Dielectric(_) => Lobe::Transmission,
         }
     }
+
+    /// Non zero id derived from the material type and parameters, equal
+    /// materials share it.
+    pub fn id(&self) -> u32 {
+        let (kind, params) = match self {
+            Material::Lambertian(lamb) => {
+                (1u64, [lamb.albedo.x_, lamb.albedo.y_, lamb.albedo.z_, 0.])
+            }
+            Material::Metal(met) => (2, [met.albedo.x_, met.albedo.y_, met.albedo.z_, met.fuzz]),
+            Material::Dielectric(diel) => (3, [diel.refraction, 0., 0., 0.]),
+            Material::DiffuseLight(light) => (
+                4,
+                [light.emission.x_, light.emission.y_, light.emission.z_, 0.],
+            ),
+        };
+
+        // FNV-1a over the parameter bits.
+        let mut hash = 0xcbf29ce484222325u64;
+        for word in std::iter::once(kind).chain(params.iter().map(|p| p.to_bits())) {
+            for byte in word.to_le_bytes().iter() {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        ((hash ^ (hash >> 32)) as u32).max(1)
+    }
+}
+
+fn reflect(v: &Vec3, normal: &Vec3) -> Vec3 {
+    v - 2. * Vec3::dot(v, normal) * normal
 }
 
 #[derive(Clone, Copy)]
@@ -47,9 +183,8 @@ pub struct Lambertian {
 }
 
 impl Lambertian {
-    fn scatter<R: Rng>(&self, _r: &Ray, hit_record: &HitRecord, rng: &mut R) -> Ray {
-        let scatter_dir =
-            hit_record.out_normal + Vec3::random_in_hemisphere(&hit_record.out_normal, rng);
+    fn scatter(&self, _r: &Ray, hit_record: &HitRecord, u2: (f64, f64)) -> Ray {
+        let scatter_dir = warp::cosine_hemisphere(&hit_record.out_normal, u2);
 
         Ray::new(hit_record.hit_point, scatter_dir)
     }
@@ -67,13 +202,11 @@ pub struct Metal {
 }
 
 impl Metal {
-    fn scatter<R: Rng>(&self, r: &Ray, hit_record: &HitRecord, rng: &mut R) -> Option<Ray> {
-        let reflect = |v: &Vec3, norm: &Vec3| -> Vec3 { v - 2. * Vec3::dot(v, norm) * norm };
-
+    fn scatter(&self, r: &Ray, hit_record: &HitRecord, u: f64, u2: (f64, f64)) -> Option<Ray> {
         let reflection = reflect(&Vec3::unit_vector(r.direction()), &hit_record.out_normal);
         let scatter = Ray::new(
             hit_record.hit_point,
-            reflection + self.fuzz * Vec3::random_in_unit_sphere(rng),
+            reflection + self.fuzz * warp::unit_ball(u, u2),
         );
         if Vec3::dot(&scatter.direction(), &hit_record.out_normal) > 0. {
             Some(scatter)
@@ -93,8 +226,7 @@ pub struct Dielectric {
 }
 
 impl Dielectric {
-    fn scatter<R: Rng>(&self, r: &Ray, hit_record: &HitRecord, rng: &mut R) -> Ray {
-        let reflect = |v: &Vec3, norm: &Vec3| -> Vec3 { v - 2. * Vec3::dot(v, norm) * norm };
+    fn scatter(&self, r: &Ray, hit_record: &HitRecord, u: f64) -> Ray {
         let refraction_ratio = if hit_record.front_face {
             1. / self.refraction
         } else {
@@ -108,7 +240,7 @@ impl Dielectric {
 
         let cannot_refract = refraction_ratio * sin_theta > 1.;
         let direction =
-            if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > rng.gen() {
+            if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > u {
                 reflect(&unit_dir, &hit_record.out_normal)
             } else {
                 self.refract(&unit_dir, &hit_record.out_normal, refraction_ratio)
@@ -117,9 +249,42 @@ impl Dielectric {
         Ray::new(hit_record.hit_point, direction)
     }
 
+    // Both directions with their Fresnel weights, reflection only past the
+    // critical angle.
+    fn lobes(&self, r: &Ray, hit_record: &HitRecord) -> Vec<(Ray, Vec3)> {
+        let refraction_ratio = if hit_record.front_face {
+            1. / self.refraction
+        } else {
+            self.refraction
+        };
+        let unit_dir = Vec3::unit_vector(r.direction());
+        let cos_theta = Vec3::dot(&(-unit_dir), &hit_record.out_normal).min(1.);
+        let sin_theta = (1. - cos_theta * cos_theta).sqrt();
+
+        let reflected = Ray::new(
+            hit_record.hit_point,
+            reflect(&unit_dir, &hit_record.out_normal),
+        );
+        if refraction_ratio * sin_theta > 1. {
+            return vec![(reflected, Vec3::new(1., 1., 1.))];
+        }
+        let reflectance = Dielectric::reflectance(cos_theta, refraction_ratio);
+        let refracted = Ray::new(
+            hit_record.hit_point,
+            self.refract(&unit_dir, &hit_record.out_normal, refraction_ratio),
+        );
+        vec![
+            (reflected, Vec3::new(reflectance, reflectance, reflectance)),
+            (
+                refracted,
+                Vec3::new(1. - reflectance, 1. - reflectance, 1. - reflectance),
+            ),
+        ]
+    }
+
     fn refract(&self, uv: &Vec3, n: &Vec3, eta: f64) -> Vec3 {
         // minimum between dot product and 1.
-        let cos_theta = Vec3::dot(&(-uv), &n).min(1.);
+        let cos_theta = Vec3::dot(&(-uv), n).min(1.);
         let out_orthogonal = eta * (uv + cos_theta * n);
         let out_parallel = -(1. - Vec3::dot(&out_orthogonal, &out_orthogonal))
             .abs()
@@ -135,3 +300,8 @@ impl Dielectric {
         r0 + (1. - r0) * (1. - cosi).powf(5.)
     }
 }
+
+#[derive(Clone, Copy)]
+pub struct DiffuseLight {
+    pub emission: Vec3,
+}
@@ -1,6 +1,17 @@
+pub mod aabb;
+pub mod aperture;
+pub mod camera;
+pub mod csg;
+pub mod cuboid;
+pub mod film;
+pub mod filter;
+pub mod heightfield;
 pub mod hitable;
+pub mod light;
 pub mod material;
 pub mod ray;
+pub mod sdf;
 pub mod sphere;
+pub mod tiles;
 pub mod vec3;
 pub mod viewport;
@@ -0,0 +1,175 @@
+use crate::stats;
+use crate::structs::aabb::Aabb;
+use crate::structs::hitable::{HitRecord, Hitable};
+use crate::structs::material::Material;
+use crate::structs::ray::Ray;
+use crate::structs::vec3::{Point3, Vec3};
+
+/// Signed distance to a surface, negative inside the shape.
+pub trait DistanceField: Send + Sync {
+    fn distance(&self, p: Point3) -> f64;
+}
+
+impl<F: Fn(Point3) -> f64 + Send + Sync> DistanceField for F {
+    fn distance(&self, p: Point3) -> f64 {
+        self(p)
+    }
+}
+
+pub fn sphere(center: Point3, radius: f64) -> impl DistanceField {
+    move |p: Point3| (p - center).length() - radius
+}
+
+pub fn rounded_box(center: Point3, half_size: Vec3, rounding: f64) -> impl DistanceField {
+    move |p: Point3| {
+        let local = p - center;
+        let q = Vec3::new(
+            local.x_.abs() - half_size.x_ + rounding,
+            local.y_.abs() - half_size.y_ + rounding,
+            local.z_.abs() - half_size.z_ + rounding,
+        );
+        let outside = Vec3::new(q.x_.max(0.), q.y_.max(0.), q.z_.max(0.)).length();
+        let inside = q.x_.max(q.y_).max(q.z_).min(0.);
+        outside + inside - rounding
+    }
+}
+
+/// Torus lying in the xz plane.
+pub fn torus(center: Point3, major_radius: f64, minor_radius: f64) -> impl DistanceField {
+    move |p: Point3| {
+        let local = p - center;
+        let ring = (local.x_ * local.x_ + local.z_ * local.z_).sqrt() - major_radius;
+        (ring * ring + local.y_ * local.y_).sqrt() - minor_radius
+    }
+}
+
+pub fn capsule(a: Point3, b: Point3, radius: f64) -> impl DistanceField {
+    move |p: Point3| {
+        let pa = p - a;
+        let ba = b - a;
+        let h = num::clamp(Vec3::dot(&pa, &ba) / Vec3::dot(&ba, &ba), 0., 1.);
+        (pa - h * ba).length() - radius
+    }
+}
+
+pub fn union(a: impl DistanceField, b: impl DistanceField) -> impl DistanceField {
+    move |p: Point3| a.distance(p).min(b.distance(p))
+}
+
+pub fn intersection(a: impl DistanceField, b: impl DistanceField) -> impl DistanceField {
+    move |p: Point3| a.distance(p).max(b.distance(p))
+}
+
+pub fn subtraction(a: impl DistanceField, b: impl DistanceField) -> impl DistanceField {
+    move |p: Point3| a.distance(p).max(-b.distance(p))
+}
+
+/// Polynomial smooth minimum, `k` is the size of the blending region.
+pub fn smooth_union(a: impl DistanceField, b: impl DistanceField, k: f64) -> impl DistanceField {
+    move |p: Point3| {
+        let (da, db) = (a.distance(p), b.distance(p));
+        let h = num::clamp(0.5 + 0.5 * (db - da) / k, 0., 1.);
+        db + (da - db) * h - k * h * (1. - h)
+    }
+}
+
+pub fn smooth_subtraction(
+    a: impl DistanceField,
+    b: impl DistanceField,
+    k: f64,
+) -> impl DistanceField {
+    move |p: Point3| {
+        let (da, db) = (a.distance(p), b.distance(p));
+        let h = num::clamp(0.5 - 0.5 * (da + db) / k, 0., 1.);
+        da + (-db - da) * h + k * h * (1. - h)
+    }
+}
+
+/// Hitable rendering a distance field by sphere tracing inside `bounds`.
+pub struct Sdf {
+    field: Box<dyn DistanceField>,
+    bounds: Aabb,
+    material: Material,
+    epsilon: f64,
+    max_steps: u32,
+}
+
+impl Sdf {
+    pub fn new(field: Box<dyn DistanceField>, bounds: Aabb, material: Material) -> Sdf {
+        Sdf {
+            field,
+            bounds,
+            material,
+            epsilon: 1e-5,
+            max_steps: 256,
+        }
+    }
+
+    pub fn with_precision(mut self, epsilon: f64, max_steps: u32) -> Sdf {
+        self.epsilon = epsilon;
+        self.max_steps = max_steps;
+        self
+    }
+
+    fn normal(&self, p: Point3) -> Vec3 {
+        let h = 10. * self.epsilon;
+        let dx = Vec3::new(h, 0., 0.);
+        let dy = Vec3::new(0., h, 0.);
+        let dz = Vec3::new(0., 0., h);
+        let gradient = Vec3::new(
+            self.field.distance(p + dx) - self.field.distance(p - dx),
+            self.field.distance(p + dy) - self.field.distance(p - dy),
+            self.field.distance(p + dz) - self.field.distance(p - dz),
+        );
+        Vec3::unit_vector(gradient)
+    }
+}
+
+impl Hitable for Sdf {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::record(|stats| stats.primitive_tests += 1);
+        let (t_enter, t_exit) = self.bounds.intersect(r)?;
+        let t_end = t_exit.min(t_max);
+        let mut t = t_enter.max(t_min);
+        if t >= t_end {
+            return None;
+        }
+
+        let dir_length = r.direction().length();
+
+        // March on the side of the surface the ray starts on. Rays leaving
+        // a surface start right on it, so use the direction to pick the side.
+        let start = r.point_at(t);
+        let start_distance = self.field.distance(start);
+        let side = if start_distance.abs() < 10. * self.epsilon {
+            Vec3::dot(&r.direction(), &self.normal(start)).signum()
+        } else {
+            start_distance.signum()
+        };
+
+        for step in 0..self.max_steps {
+            let p = r.point_at(t);
+            let distance = side * self.field.distance(p);
+            if distance < self.epsilon && step > 0 {
+                return Some(HitRecord::new(
+                    t,
+                    p,
+                    self.normal(p),
+                    r.direction(),
+                    self.material,
+                ));
+            }
+
+            t += distance.max(self.epsilon) / dir_length;
+            if t >= t_end {
+                break;
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
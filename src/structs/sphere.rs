@@ -1,3 +1,6 @@
+use crate::stats;
+use crate::structs::aabb::Aabb;
+use crate::structs::csg::{Crossing, Solid, Span};
 use crate::structs::hitable::{HitRecord, Hitable};
 use crate::structs::material::Material;
 use crate::structs::ray::Ray;
@@ -21,6 +24,7 @@ impl Sphere {
 
 impl Hitable for Sphere {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::record(|stats| stats.primitive_tests += 1);
         let oc = r.origin() - self.center;
         let a = Vec3::dot(&r.direction(), &r.direction());
         let b = Vec3::dot(&oc, &r.direction());
@@ -50,4 +54,33 @@ impl Hitable for Sphere {
         }
         None
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+impl Solid for Sphere {
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        stats::record(|stats| stats.primitive_tests += 1);
+        let oc = r.origin() - self.center;
+        let a = Vec3::dot(&r.direction(), &r.direction());
+        let b = Vec3::dot(&oc, &r.direction());
+        let c = Vec3::dot(&oc, &oc) - self.radius * self.radius;
+        let discr = b.powf(2f64) - a * c;
+        if discr <= 0. {
+            return Vec::new();
+        }
+
+        let crossing = |t: f64| Crossing {
+            t,
+            normal: (r.point_at(t) - self.center) / self.radius,
+            material: self.material,
+        };
+        vec![Span {
+            enter: crossing((-b - discr.sqrt()) / a),
+            exit: crossing((-b + discr.sqrt()) / a),
+        }]
+    }
 }
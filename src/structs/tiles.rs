@@ -0,0 +1,115 @@
+/// Order tiles are handed out to the render threads in.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TileOrder {
+    /// Row by row from the top left.
+    Scanline,
+    /// Outwards from the center of the image, where the subject usually is.
+    Spiral,
+    /// Along a Hilbert curve, consecutive tiles are always neighbours.
+    Hilbert,
+}
+
+impl TileOrder {
+    pub fn from_name(name: &str) -> Option<TileOrder> {
+        match name.to_ascii_lowercase().as_str() {
+            "scanline" => Some(TileOrder::Scanline),
+            "spiral" => Some(TileOrder::Spiral),
+            "hilbert" => Some(TileOrder::Hilbert),
+            _ => None,
+        }
+    }
+}
+
+/// Rectangle of pixels rendered as one unit of work.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tile {
+    pub x0: u32,
+    pub y0: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Covers the image with tiles of `size` pixels, the last row and column
+/// are cut to fit, in the given order.
+pub fn tiles(width: u32, height: u32, size: u32, order: TileOrder) -> Vec<Tile> {
+    let size = size.max(1);
+    let (nx, ny) = (width.div_ceil(size), height.div_ceil(size));
+    let tile = |tx: u32, ty: u32| Tile {
+        x0: tx * size,
+        y0: ty * size,
+        width: size.min(width - tx * size),
+        height: size.min(height - ty * size),
+    };
+
+    let positions: Vec<(u32, u32)> = match order {
+        TileOrder::Scanline => (0..ny)
+            .flat_map(|ty| (0..nx).map(move |tx| (tx, ty)))
+            .collect(),
+        TileOrder::Spiral => spiral(nx, ny),
+        TileOrder::Hilbert => {
+            let n = nx.max(ny).next_power_of_two();
+            (0..n as u64 * n as u64)
+                .map(|d| hilbert_position(n, d))
+                .filter(|(tx, ty)| *tx < nx && *ty < ny)
+                .collect()
+        }
+    };
+    positions.into_iter().map(|(tx, ty)| tile(tx, ty)).collect()
+}
+
+// Square spiral around the center tile, positions outside the grid are skipped.
+fn spiral(nx: u32, ny: u32) -> Vec<(u32, u32)> {
+    let total = (nx * ny) as usize;
+    let mut result = Vec::with_capacity(total);
+    if total == 0 {
+        return result;
+    }
+    let (mut x, mut y) = (((nx - 1) / 2) as i64, ((ny - 1) / 2) as i64);
+    let (mut dx, mut dy) = (1i64, 0i64);
+    let mut leg = 1;
+
+    let push = |x: i64, y: i64, result: &mut Vec<(u32, u32)>| {
+        if x >= 0 && y >= 0 && x < nx as i64 && y < ny as i64 {
+            result.push((x as u32, y as u32));
+        }
+    };
+    push(x, y, &mut result);
+    while result.len() < total {
+        // Every leg is walked twice before growing by one.
+        for _ in 0..2 {
+            for _ in 0..leg {
+                x += dx;
+                y += dy;
+                push(x, y, &mut result);
+            }
+            let turned = (-dy, dx);
+            dx = turned.0;
+            dy = turned.1;
+        }
+        leg += 1;
+    }
+    result
+}
+
+// Position of point `d` along the Hilbert curve filling an `n` by `n` grid.
+fn hilbert_position(n: u32, d: u64) -> (u32, u32) {
+    let (mut x, mut y) = (0u64, 0u64);
+    let mut t = d;
+    let mut s = 1u64;
+    while s < n as u64 {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x as u32, y as u32)
+}
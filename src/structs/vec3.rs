@@ -53,7 +53,7 @@ impl Vec3 {
 
     pub fn random_in_hemisphere<R: Rng>(normal: &Vec3, rng: &mut R) -> Vec3 {
         let inside = Vec3::random_in_unit_sphere(rng);
-        if Vec3::dot(&inside, &normal) > 0. {
+        if Vec3::dot(&inside, normal) > 0. {
             inside
         } else {
             -inside
@@ -1,22 +1,257 @@
+use crate::integrator::{Integrator, PathIntegrator};
+use crate::sampler::{Sampler, SobolSampler};
+use crate::stats::{self, RenderStats};
+use crate::structs::camera::{Camera, PerspectiveCamera};
+use crate::structs::film::{AovSample, Film, FilmTile, Radiance};
+use crate::structs::filter::Filter;
 use crate::structs::hitable::{HitList, Hitable};
-use crate::structs::ray::Ray;
+use crate::structs::tiles::{self, Tile, TileOrder};
 use crate::structs::vec3::{Point3, Vec3};
 
-use rand::Rng;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use rayon::prelude::*;
+/// Keeps sampling the pixels whose noise is above `threshold`, as measured
+/// by `FilmPixel::relative_error`, up to `max_samples`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdaptiveSampling {
+    pub threshold: f64,
+    pub max_samples: u32,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> AdaptiveSampling {
+        AdaptiveSampling {
+            threshold: 0.01,
+            max_samples: 1024,
+        }
+    }
+}
+
+// Pixels with a noisy pixel in their 3x3 neighbourhood, the estimate of a
+// single pixel is too noisy itself to stop on.
+fn noisy_pixels(film: &Film, threshold: f64) -> Vec<bool> {
+    let (width, height) = (film.width() as i64, film.height() as i64);
+    let noisy: Vec<bool> = film
+        .pixels()
+        .iter()
+        .map(|pixel| pixel.relative_error() > threshold)
+        .collect();
+
+    (0..width * height)
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            (y - 1..=y + 1).any(|ny| {
+                (x - 1..=x + 1).any(|nx| {
+                    nx >= 0
+                        && ny >= 0
+                        && nx < width
+                        && ny < height
+                        && noisy[(ny * width + nx) as usize]
+                })
+            })
+        })
+        .collect()
+}
+
+/// How `Viewport::render_progressive` splits the work into passes, when it
+/// stops and how often it takes snapshots and checkpoints.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progressive {
+    /// Samples added to every pixel per pass.
+    pub pass_samples: u32,
+    /// Samples every pixel ends up with.
+    pub target_samples: u32,
+    /// Stop handing out tiles once this has passed.
+    pub time_budget: Option<Duration>,
+    pub snapshot_passes: Option<u32>,
+    pub snapshot_interval: Option<Duration>,
+    /// Checkpoints are taken after every pass unless this is set.
+    pub checkpoint_interval: Option<Duration>,
+}
+
+impl Progressive {
+    pub fn new(pass_samples: u32, target_samples: u32) -> Progressive {
+        Progressive {
+            pass_samples,
+            target_samples,
+            time_budget: None,
+            snapshot_passes: None,
+            snapshot_interval: None,
+            checkpoint_interval: None,
+        }
+    }
+
+    pub fn with_time_budget(mut self, budget: Duration) -> Progressive {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Snapshot after every `passes` passes.
+    pub fn with_snapshot_passes(mut self, passes: u32) -> Progressive {
+        self.snapshot_passes = Some(passes);
+        self
+    }
+
+    /// Snapshot after the first tile that ends `interval` after the last snapshot.
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Progressive {
+        self.snapshot_interval = Some(interval);
+        self
+    }
+
+    /// Checkpoint after the first tile that ends `interval` after the last
+    /// checkpoint, rather than after every pass.
+    pub fn with_checkpoint_interval(mut self, interval: Duration) -> Progressive {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+}
+
+/// How far a render has come, reported after every tile.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    /// Pass being rendered, counting from 1. Plain renders have a single one.
+    pub pass: u32,
+    /// Tiles done in this pass.
+    pub tiles: usize,
+    pub tiles_per_pass: usize,
+    /// Samples taken since the render started.
+    pub samples: u64,
+    /// Samples planned in total, an upper bound with adaptive sampling.
+    pub total_samples: u64,
+    pub elapsed: Duration,
+    /// Time left at the current sample rate, capped by the time budget.
+    pub eta: Option<Duration>,
+}
+
+impl Progress {
+    /// Share of the planned samples taken, in `[0, 1]`.
+    pub fn fraction(&self) -> f64 {
+        if self.total_samples == 0 {
+            1.
+        } else {
+            (self.samples as f64 / self.total_samples as f64).min(1.)
+        }
+    }
+}
+
+pub type ProgressCallback = Box<dyn Fn(&Progress) + Send + Sync>;
+
+/// Stops a render from another thread. Tiles being rendered are finished,
+/// no new ones are started and the render returns the film as it is.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Counts the work done for `Progress`.
+struct ProgressTracker {
+    start: Instant,
+    time_budget: Option<Duration>,
+    pass: u32,
+    tiles: usize,
+    tiles_per_pass: usize,
+    samples: u64,
+    total_samples: u64,
+}
+
+impl ProgressTracker {
+    fn new(
+        tiles_per_pass: usize,
+        total_samples: u64,
+        time_budget: Option<Duration>,
+    ) -> ProgressTracker {
+        ProgressTracker {
+            start: Instant::now(),
+            time_budget,
+            pass: 1,
+            tiles: 0,
+            tiles_per_pass,
+            samples: 0,
+            total_samples,
+        }
+    }
+
+    fn next_pass(&mut self) {
+        self.pass += 1;
+        self.tiles = 0;
+    }
+
+    // Counts the samples `plan` gave the pixels of `tile`.
+    fn tile_done(&mut self, tile: &FilmTile, plan: &[(u32, u32)], img_width: u32) -> Progress {
+        self.tiles += 1;
+        for y in tile.y0..tile.y0 + tile.height {
+            let row = (y * img_width) as usize;
+            self.samples += plan[row + tile.x0 as usize..row + (tile.x0 + tile.width) as usize]
+                .iter()
+                .map(|(samples, _)| *samples as u64)
+                .sum::<u64>();
+        }
+
+        let elapsed = self.start.elapsed();
+        let eta = if self.samples == 0 {
+            None
+        } else {
+            let remaining = self.total_samples.saturating_sub(self.samples);
+            let eta = elapsed.mul_f64(remaining as f64 / self.samples as f64);
+            Some(match self.time_budget {
+                Some(budget) => eta.min(budget.saturating_sub(elapsed)),
+                None => eta,
+            })
+        };
+        Progress {
+            pass: self.pass,
+            tiles: self.tiles,
+            tiles_per_pass: self.tiles_per_pass,
+            samples: self.samples,
+            total_samples: self.total_samples,
+            elapsed,
+            eta,
+        }
+    }
+}
 
 pub struct Viewport {
-    lens_radius_: f64,
-    origin_: Point3,
-    horizontal_: Vec3,
-    vertical_: Vec3,
-    lower_left_corner_: Vec3,
-    u_: Vec3,
-    v_: Vec3,
+    camera: Box<dyn Camera + Send + Sync>,
+    previous_camera: Option<Box<dyn Camera + Send + Sync>>,
+    aovs: bool,
+    filter: Filter,
+    sampler: Box<dyn Sampler + Send + Sync>,
+    seed: u64,
+    adaptive: Option<AdaptiveSampling>,
+    tile_size: u32,
+    tile_order: TileOrder,
+    progress: Option<ProgressCallback>,
+    cancel: CancelToken,
+    stats: Option<Mutex<RenderStats>>,
+    integrator: Box<dyn Integrator + Send + Sync>,
+}
+
+// Tiles done rendering, merged into the film in the order they were handed
+// out as soon as all tiles before them are merged.
+struct TileMerge<'a> {
+    film: &'a mut Film,
+    merged: &'a mut (dyn FnMut(&Film, &FilmTile) + Send),
+    finished: BTreeMap<usize, FilmTile>,
+    next: usize,
 }
 
 impl Viewport {
+    /// Viewport with a thin lens perspective camera.
     pub fn new(
         lookfrom: Point3,
         lookat: Point3,
@@ -26,62 +261,127 @@ impl Viewport {
         aperture: f64,
         focus_dist: f64,
     ) -> Viewport {
-        let theta = vfov.to_radians();
-        let h = (theta / 2.).tan();
-        let view_height = h;
-        let view_width = aspect_ratio * view_height;
-
-        let w = Vec3::unit_vector(lookfrom - lookat);
-        let u = Vec3::unit_vector(Vec3::cross(&vup, &w));
-        let v = Vec3::cross(&w, &u);
+        Viewport::with_camera(Box::new(PerspectiveCamera::new(
+            lookfrom,
+            lookat,
+            vup,
+            vfov,
+            aspect_ratio,
+            aperture,
+            focus_dist,
+        )))
+    }
 
+    pub fn with_camera(camera: Box<dyn Camera + Send + Sync>) -> Viewport {
         Viewport {
-            lens_radius_: aperture / 2.,
-            origin_: lookfrom,
-            horizontal_: 2. * focus_dist * view_width * u,
-            vertical_: 2. * focus_dist * view_height * v,
-            lower_left_corner_: (lookfrom
-                - view_width * focus_dist * u
-                - view_height * focus_dist * v
-                - focus_dist * w),
-            u_: u,
-            v_: v,
-        }
-    }
-
-    pub fn send_ray<R: Rng>(&self, s: f64, t: f64, rng: &mut R) -> Ray {
-        let rd = self.lens_radius_ * Vec3::random_in_unit_disk(rng);
-        let offset = rd.x_ * self.u_ + rd.y_ * self.v_;
-
-        Ray::new(
-            self.origin_ + offset,
-            self.lower_left_corner_ + s * self.horizontal_ + t * self.vertical_
-                - self.origin_
-                - offset,
-        )
-    }
-
-    fn ray_col<R: Rng>(r: &Ray, scene: &HitList, rng: &mut R, depth: u32) -> Vec3 {
-        if depth != 0 {
-            match scene.hit(r, 0.001, f64::MAX) {
-                Some(hit_rec) => {
-                    if let Some(scatter_vec) = hit_rec.material.scatter(&r, &hit_rec, rng) {
-                        hit_rec.material.attenuation()
-                            * Viewport::ray_col(&scatter_vec, &scene, rng, depth - 1)
-                    } else {
-                        Vec3::new(0., 0., 0.)
-                    }
-                }
-                None => {
-                    let t = 0.5 * (Vec3::unit_vector(r.direction()).y_ + 1.);
-                    (1. - t) * Vec3::new(1., 1., 1.) + t * Vec3::new(0.5, 0.7, 1.0)
-                }
-            }
+            camera,
+            previous_camera: None,
+            aovs: false,
+            filter: Filter::default(),
+            sampler: Box::new(SobolSampler::new()),
+            seed: 0,
+            adaptive: None,
+            tile_size: 32,
+            tile_order: TileOrder::Spiral,
+            progress: None,
+            cancel: CancelToken::new(),
+            stats: None,
+            integrator: Box::new(PathIntegrator::new()),
+        }
+    }
+
+    /// Sampler every thread takes a copy of.
+    pub fn with_sampler(mut self, sampler: Box<dyn Sampler + Send + Sync>) -> Viewport {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Pixel filter of the films `render` creates.
+    pub fn with_filter(mut self, filter: Filter) -> Viewport {
+        self.filter = filter;
+        self
+    }
+
+    /// Seed of the sampler, equal seeds render bit identical images.
+    pub fn with_seed(mut self, seed: u64) -> Viewport {
+        self.seed = seed;
+        self
+    }
+
+    /// Sample noisy pixels more in `render`, the samples passed to it are
+    /// taken everywhere first.
+    pub fn with_adaptive_sampling(mut self, adaptive: AdaptiveSampling) -> Viewport {
+        self.adaptive = Some(adaptive);
+        self
+    }
+
+    /// Render in square tiles of `size` pixels, handed out to the threads
+    /// in `order`.
+    pub fn with_tiles(mut self, size: u32, order: TileOrder) -> Viewport {
+        self.tile_size = size.max(1);
+        self.tile_order = order;
+        self
+    }
+
+    /// Called with the progress of every render after each tile, from the
+    /// render threads.
+    pub fn with_progress<F: Fn(&Progress) + Send + Sync + 'static>(
+        mut self,
+        callback: F,
+    ) -> Viewport {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Token that cancels the render running on this viewport. Cancelling is
+    /// final, later renders return right away.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Algorithm finding the light along camera rays, a path tracer unless
+    /// set.
+    pub fn with_integrator(mut self, integrator: Box<dyn Integrator + Send + Sync>) -> Viewport {
+        self.integrator = integrator;
+        self
+    }
+
+    /// Count rays, intersection tests and path lengths of every render,
+    /// see `stats`.
+    pub fn with_stats(mut self) -> Viewport {
+        self.stats = Some(Mutex::new(RenderStats::default()));
+        self
+    }
+
+    /// Statistics of all renders so far, when asked for with `with_stats`.
+    pub fn stats(&self) -> Option<RenderStats> {
+        self.stats.as_ref().map(|stats| *stats.lock().unwrap())
+    }
+
+    /// Record AOVs of the first hit along with the image.
+    pub fn with_aovs(mut self) -> Viewport {
+        self.aovs = true;
+        self
+    }
+
+    /// Camera of the previous frame, motion vectors are measured against it.
+    pub fn with_previous_camera(mut self, camera: Box<dyn Camera + Send + Sync>) -> Viewport {
+        self.previous_camera = Some(camera);
+        self
+    }
+
+    /// Empty film of the given size, with the pixel filter and AOVs `render` uses.
+    pub fn film(&self, img_width: u32, img_height: u32) -> Film {
+        let film = Film::new(img_width, img_height).with_filter(self.filter);
+        if self.aovs {
+            film.with_aovs()
         } else {
-            Vec3::new(0., 0., 0.)
+            film
         }
     }
 
+    /// Renders `scene` into a new film. When cancelled, the film is returned
+    /// with the samples taken so far.
     pub fn render(
         &self,
         img_width: u32,
@@ -89,34 +389,396 @@ impl Viewport {
         samples: u32,
         ray_depth: u32,
         scene: HitList,
-    ) -> Vec<u8> {
-        (0..img_height)
-            .into_par_iter()
-            .rev()
-            .map_init(rand::thread_rng, |rng, j| {
-                let mut result = Vec::with_capacity(3 * img_width as usize);
-                for i in 0..img_width {
-                    let mut col = Vec3::new(0., 0., 0.);
-                    for _ns in 0..samples {
-                        let u = (i as f64 + rng.gen::<f64>()) / img_width as f64;
-                        let v = (j as f64 + rng.gen::<f64>()) / img_height as f64;
-                        let r = self.send_ray(u, v, rng);
-                        col = col + Viewport::ray_col(&r, &scene, rng, ray_depth);
+    ) -> Film {
+        let mut film = self.film(img_width, img_height);
+        match &self.adaptive() {
+            // Takes `samples` everywhere, then as many again where it is still noisy.
+            Some(adaptive) => {
+                let progressive = Progressive::new(samples, adaptive.max_samples.max(samples));
+                self.render_progressive(
+                    &mut film,
+                    &progressive,
+                    ray_depth,
+                    &scene,
+                    |_, _| {},
+                    |_, _| {},
+                );
+            }
+            None => self.render_into(&mut film, samples, ray_depth, &scene),
+        }
+        film
+    }
+
+    /// Adds `samples` more samples to every pixel of `film`, continuing the
+    /// sample sequence of each pixel where it stopped.
+    pub fn render_into(&self, film: &mut Film, samples: u32, ray_depth: u32, scene: &HitList) {
+        let targets: Vec<u32> = film
+            .pixels()
+            .iter()
+            .map(|pixel| pixel.samples + samples)
+            .collect();
+        let step = self.integrator.pass_samples().unwrap_or(samples).max(1);
+        let img_width = film.width();
+        let mut tracker = ProgressTracker::new(
+            self.tile_count(film),
+            samples as u64 * targets.len() as u64,
+            None,
+        );
+
+        let mut done = 0;
+        while done < samples {
+            let pass = step.min(samples - done);
+            let plan: Vec<(u32, u32)> = targets.iter().map(|target| (pass, *target)).collect();
+            if done > 0 {
+                tracker.next_pass();
+            }
+            let complete =
+                self.render_pass(film, &plan, ray_depth, scene, &|| false, &mut |_, tile| {
+                    self.report(tracker.tile_done(tile, &plan, img_width))
+                });
+            if !complete {
+                break;
+            }
+            done += pass;
+        }
+    }
+
+    /// Renders into `film` one pass at a time until every pixel has the
+    /// target number of samples or the time budget is spent, calling
+    /// `snapshot` with the film and the passes done as often as asked.
+    /// `checkpoint` is called the same way after every pass, or once the
+    /// checkpoint interval has passed, whatever the snapshot settings.
+    /// With adaptive sampling, pixels whose neighbourhood is no longer
+    /// noisy are skipped after the first pass.
+    ///
+    /// Passes are checked tile by tile: the time budget or cancelling can
+    /// stop a pass half way and interval snapshots are taken between tiles. The film
+    /// stays consistent either way, every pixel holds whole samples only.
+    ///
+    /// Returns the number of passes rendered, counting a stopped one.
+    pub fn render_progressive<F, C>(
+        &self,
+        film: &mut Film,
+        progressive: &Progressive,
+        ray_depth: u32,
+        scene: &HitList,
+        mut snapshot: F,
+        mut checkpoint: C,
+    ) -> u32
+    where
+        F: FnMut(&Film, u32) + Send,
+        C: FnMut(&Film, u32) + Send,
+    {
+        let start = Instant::now();
+        let mut last_snapshot = start;
+        let mut last_checkpoint = start;
+        let target = progressive.target_samples;
+        let step = match self.integrator.pass_samples() {
+            Some(samples) => progressive.pass_samples.min(samples),
+            None => progressive.pass_samples,
+        }
+        .max(1);
+        let plan_pixel = |samples: u32| (step.min(target.saturating_sub(samples)), target);
+        let out_of_time = || {
+            progressive
+                .time_budget
+                .is_some_and(|budget| start.elapsed() >= budget)
+        };
+
+        let mut plan: Vec<(u32, u32)> = film
+            .pixels()
+            .iter()
+            .map(|pixel| plan_pixel(pixel.samples))
+            .collect();
+        let mut passes = 0;
+        let img_width = film.width();
+        let mut tracker = ProgressTracker::new(
+            self.tile_count(film),
+            film.pixels()
+                .iter()
+                .map(|pixel| target.saturating_sub(pixel.samples) as u64)
+                .sum(),
+            progressive.time_budget,
+        );
+
+        while plan.iter().any(|(samples, _)| *samples > 0) {
+            if passes > 0 {
+                tracker.next_pass();
+            }
+            let complete = self.render_pass(
+                film,
+                &plan,
+                ray_depth,
+                scene,
+                &out_of_time,
+                &mut |film, tile| {
+                    self.report(tracker.tile_done(tile, &plan, img_width));
+                    if progressive
+                        .snapshot_interval
+                        .is_some_and(|interval| last_snapshot.elapsed() >= interval)
+                    {
+                        snapshot(film, passes);
+                        last_snapshot = Instant::now();
                     }
+                    if progressive
+                        .checkpoint_interval
+                        .is_some_and(|interval| last_checkpoint.elapsed() >= interval)
+                    {
+                        checkpoint(film, passes);
+                        last_checkpoint = Instant::now();
+                    }
+                },
+            );
+            passes += 1;
+
+            if complete && progressive.checkpoint_interval.is_none() {
+                checkpoint(film, passes);
+            }
+
+            if complete
+                && progressive
+                    .snapshot_passes
+                    .is_some_and(|every| passes % every.max(1) == 0)
+            {
+                snapshot(film, passes);
+                last_snapshot = Instant::now();
+            }
+            if !complete || out_of_time() {
+                break;
+            }
+
+            let noisy = self
+                .adaptive()
+                .map(|adaptive| noisy_pixels(film, adaptive.threshold));
+            for (index, (plan, pixel)) in plan.iter_mut().zip(film.pixels()).enumerate() {
+                *plan = match &noisy {
+                    Some(noisy) if !noisy[index] => (0, target),
+                    _ => plan_pixel(pixel.samples),
+                };
+            }
+        }
+        passes
+    }
+
+    // Adaptive sampling, unless the integrator leaves the pixels black and
+    // there is no noise to measure.
+    fn adaptive(&self) -> Option<AdaptiveSampling> {
+        self.adaptive.filter(|_| !self.integrator.splats_only())
+    }
 
-                    col = col / samples as f64;
+    fn tile_count(&self, film: &Film) -> usize {
+        let size = self.tile_size;
+        (film.width().div_ceil(size) * film.height().div_ceil(size)) as usize
+    }
 
-                    let ir = (256. * num::clamp(col.x_.sqrt(), 0., 0.999)) as u8;
-                    let ig = (256. * num::clamp(col.y_.sqrt(), 0., 0.999)) as u8;
-                    let ib = (256. * num::clamp(col.z_.sqrt(), 0., 0.999)) as u8;
+    fn report(&self, progress: Progress) {
+        if let Some(callback) = &self.progress {
+            callback(&progress);
+        }
+    }
 
-                    result.push(ir);
-                    result.push(ig);
-                    result.push(ib);
+    // `plan` holds the samples to add to every pixel and the total number
+    // it is planned to get, row by row.
+    //
+    // Every thread takes the next tile in order until they run out or
+    // `stop` says so. Tiles are merged in that same order, each as soon as
+    // the ones before it are, so the result does not depend on which thread
+    // finished first and the film only ever shows a coherent region grow.
+    // `merged` sees the film after every merged tile.
+    //
+    // Returns whether every tile was rendered, cancelling stops it too.
+    fn render_pass(
+        &self,
+        film: &mut Film,
+        plan: &[(u32, u32)],
+        ray_depth: u32,
+        scene: &HitList,
+        stop: &(dyn Fn() -> bool + Sync),
+        merged: &mut (dyn FnMut(&Film, &FilmTile) + Send),
+    ) -> bool {
+        let dims = (film.width(), film.height());
+        let first_samples: Vec<u32> = film.pixels().iter().map(|pixel| pixel.samples).collect();
+        let most_samples = first_samples.iter().copied().max().unwrap_or(0);
+        self.integrator.prepare(
+            scene,
+            self.camera.as_ref(),
+            self.seed,
+            most_samples,
+            ray_depth,
+        );
+        let tiles = tiles::tiles(dims.0, dims.1, self.tile_size, self.tile_order);
+        let next_tile = AtomicUsize::new(0);
+        let merge = Mutex::new(TileMerge {
+            film,
+            merged,
+            finished: BTreeMap::new(),
+            next: 0,
+        });
+
+        let start = Instant::now();
+        let worker = || {
+            let mut sampler = self.sampler.box_clone();
+            sampler.set_seed(self.seed);
+            stats::set_enabled(self.stats.is_some());
+            while !stop() && !self.cancel.is_cancelled() {
+                let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                let bounds: Tile = match tiles.get(index) {
+                    Some(bounds) => *bounds,
+                    None => break,
+                };
+                let mut tile = merge.lock().unwrap().film.tile(
+                    bounds.x0,
+                    bounds.y0,
+                    bounds.width,
+                    bounds.height,
+                );
+                self.render_tile(
+                    &mut tile,
+                    dims,
+                    &first_samples,
+                    plan,
+                    ray_depth,
+                    scene,
+                    sampler.as_mut(),
+                );
+                if let Some(total) = &self.stats {
+                    total.lock().unwrap().merge(&stats::take());
                 }
-                result
-            })
-            .flatten()
-            .collect()
+
+                let mut guard = merge.lock().unwrap();
+                let merge = &mut *guard;
+                merge.finished.insert(index, tile);
+                while let Some(tile) = merge.finished.remove(&merge.next) {
+                    merge.film.merge_tile(&tile);
+                    merge.next += 1;
+                    (merge.merged)(merge.film, &tile);
+                }
+            }
+            stats::set_enabled(false);
+        };
+        rayon::scope(|scope| {
+            for _ in 0..rayon::current_num_threads() {
+                scope.spawn(|_| worker());
+            }
+        });
+
+        if let Some(total) = &self.stats {
+            total.lock().unwrap().render_time += start.elapsed();
+        }
+
+        // Stopped early, tiles done after the first skipped one are kept.
+        let merge = merge.into_inner().unwrap();
+        for tile in merge.finished.values() {
+            merge.film.merge_tile(tile);
+        }
+        merge.next == tiles.len()
+    }
+
+    // `first_samples` and `plan` hold the samples every pixel of the image
+    // already has and the plan for it.
+    #[allow(clippy::too_many_arguments)]
+    fn render_tile(
+        &self,
+        tile: &mut FilmTile,
+        (img_width, img_height): (u32, u32),
+        first_samples: &[u32],
+        plan: &[(u32, u32)],
+        ray_depth: u32,
+        scene: &HitList,
+        sampler: &mut dyn Sampler,
+    ) {
+        let splats_only = self.integrator.splats_only();
+        for y in tile.y0..tile.y0 + tile.height {
+            for i in tile.x0..tile.x0 + tile.width {
+                let index = (y * img_width + i) as usize;
+                let first = first_samples[index];
+                let (samples, count) = plan[index];
+                for ns in first..first + samples {
+                    sampler.start_pixel_sample((i, y), ns, count);
+                    let (dx, dy) = sampler.get_2d();
+                    let film_position = (i as f64 + dx, y as f64 + dy);
+                    let u = film_position.0 / img_width as f64;
+                    let v = 1. - film_position.1 / img_height as f64;
+                    // Integrators that splat only leave the pixel black, they
+                    // get no camera ray.
+                    let camera_sample = match splats_only {
+                        true => None,
+                        false => match self.camera.send_weighted_ray(u, v, sampler) {
+                            Some(sample) => Some(sample),
+                            None => {
+                                tile.add_sample((i, y), film_position, &Radiance::zero());
+                                continue;
+                            }
+                        },
+                    };
+
+                    let mut splat = |(s, t): (f64, f64), radiance| {
+                        let x = ((s * img_width as f64) as u32).min(img_width - 1);
+                        let y = ((t * img_height as f64) as u32).min(img_height - 1);
+                        tile.add_light((x, img_height - 1 - y), radiance);
+                    };
+                    let (r, radiance, bounces) = match camera_sample {
+                        Some((r, weight)) => {
+                            stats::record(|stats| stats.camera_rays += 1);
+                            let (radiance, bounces) = self.integrator.li_splat(
+                                &r,
+                                self.camera.as_ref(),
+                                scene,
+                                sampler,
+                                ray_depth,
+                                &mut splat,
+                            );
+                            (Some(r), weight * radiance, bounces)
+                        }
+                        None => {
+                            let bounces = self.integrator.splat(
+                                self.camera.as_ref(),
+                                scene,
+                                sampler,
+                                ray_depth,
+                                &mut splat,
+                            );
+                            (None, Radiance::zero(), bounces)
+                        }
+                    };
+                    stats::record(|stats| {
+                        stats.paths += 1;
+                        stats.path_depth_sum += bounces as u64;
+                    });
+                    tile.add_sample((i, y), film_position, &radiance);
+
+                    if tile.aov_mut(i, y).is_none() {
+                        continue;
+                    }
+                    let r = match r.or_else(|| self.camera.send_ray(u, v, sampler)) {
+                        Some(r) => r,
+                        None => continue,
+                    };
+                    if let Some(aov) = tile.aov_mut(i, y) {
+                        stats::record(|stats| stats.rays += 1);
+                        if let Some(hit_rec) = scene.hit(&r, 0.001, f64::MAX) {
+                            let motion = match &self.previous_camera {
+                                Some(previous) => match previous.project(hit_rec.hit_point) {
+                                    Some((ps, pt)) => {
+                                        ((ps - u) * img_width as f64, (v - pt) * img_height as f64)
+                                    }
+                                    None => (0., 0.),
+                                },
+                                None => (0., 0.),
+                            };
+                            aov.add_sample(&AovSample {
+                                albedo: hit_rec.material.attenuation(),
+                                normal: hit_rec.out_normal,
+                                position: hit_rec.hit_point,
+                                depth: hit_rec.t * r.direction().length(),
+                                motion,
+                                material_id: hit_rec.material.id(),
+                                object_id: hit_rec.object_id,
+                            });
+                        }
+                    }
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,69 @@
+mod common;
+
+use common::{HEIGHT, RAY_DEPTH, WIDTH};
+use rusttracer::checkpoint::{self, CheckpointInfo};
+use rusttracer::structs::viewport::Progressive;
+
+use std::{env, fs, process};
+
+#[test]
+fn resuming_matches_an_uninterrupted_render() {
+    let scene = rusttracer::debugging_scene();
+    let progressive = Progressive::new(2, 6);
+    let info = CheckpointInfo {
+        settings_hash: 0,
+        sampler: String::from("sobol"),
+        seed: 7,
+    };
+    let path = env::temp_dir().join(format!("rusttracer-test-{}.checkpoint", process::id()));
+
+    // Stopped as soon as the first pass is checkpointed.
+    let viewport = common::viewport();
+    let cancel = viewport.cancel_token();
+    let mut film = viewport.film(WIDTH, HEIGHT);
+    let mut checkpoints = Vec::new();
+    viewport.render_progressive(
+        &mut film,
+        &progressive,
+        RAY_DEPTH,
+        &scene,
+        |_, _| {},
+        |film, passes| {
+            checkpoint::save(film, &info, &path).unwrap();
+            checkpoints.push(passes);
+            cancel.cancel();
+        },
+    );
+    assert_eq!(checkpoints, vec![1]);
+    assert!(path.exists());
+
+    let viewport = common::viewport();
+    let mut resumed = viewport.film(WIDTH, HEIGHT);
+    let loaded = checkpoint::load(&mut resumed, &path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), info);
+    assert!(resumed.pixels().iter().all(|pixel| pixel.samples == 2));
+    viewport.render_progressive(
+        &mut resumed,
+        &progressive,
+        RAY_DEPTH,
+        &scene,
+        |_, _| {},
+        |_, _| {},
+    );
+
+    let mut uninterrupted = viewport.film(WIDTH, HEIGHT);
+    viewport.render_progressive(
+        &mut uninterrupted,
+        &progressive,
+        RAY_DEPTH,
+        &scene,
+        |_, _| {},
+        |_, _| {},
+    );
+    assert!(uninterrupted
+        .pixels()
+        .iter()
+        .all(|pixel| pixel.samples == 6));
+    common::assert_films_equal(&resumed, &uninterrupted);
+}
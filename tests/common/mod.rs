@@ -0,0 +1,46 @@
+use rusttracer::structs::film::Film;
+use rusttracer::structs::tiles::TileOrder;
+use rusttracer::structs::vec3::{Point3, Vec3};
+use rusttracer::structs::viewport::Viewport;
+
+pub const WIDTH: u32 = 24;
+pub const HEIGHT: u32 = 16;
+pub const RAY_DEPTH: u32 = 8;
+
+/// Small seeded view of the debugging scene, in tiles small enough that
+/// there are several to share between threads.
+pub fn viewport() -> Viewport {
+    let lookfrom = Point3::new(0., 1., 3.);
+    let lookat = Point3::new(0., 0., -1.);
+    Viewport::new(
+        lookfrom,
+        lookat,
+        Vec3::new(0., 1., 0.),
+        50.,
+        WIDTH as f64 / HEIGHT as f64,
+        0.05,
+        (lookfrom - lookat).length(),
+    )
+    .with_seed(7)
+    .with_tiles(8, TileOrder::Spiral)
+}
+
+/// Asserts the films hold bit for bit the same sums.
+pub fn assert_films_equal(a: &Film, b: &Film) {
+    assert_eq!((a.width(), a.height()), (b.width(), b.height()));
+    let bits = |v: &Vec3| [v.x_.to_bits(), v.y_.to_bits(), v.z_.to_bits()];
+    for (index, (p, q)) in a.pixels().iter().zip(b.pixels()).enumerate() {
+        assert_eq!(p.samples, q.samples, "samples of pixel {}", index);
+        assert_eq!(
+            bits(&p.radiance_sum),
+            bits(&q.radiance_sum),
+            "radiance of pixel {}",
+            index
+        );
+        assert_eq!(p.weight_sum.to_bits(), q.weight_sum.to_bits());
+        assert_eq!(p.luminance_sum.to_bits(), q.luminance_sum.to_bits());
+    }
+    for (p, q) in a.light().iter().zip(b.light()) {
+        assert_eq!(bits(p), bits(q));
+    }
+}
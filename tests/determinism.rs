@@ -0,0 +1,26 @@
+mod common;
+
+use common::{HEIGHT, RAY_DEPTH, WIDTH};
+use rusttracer::structs::film::Film;
+
+use rayon::ThreadPoolBuilder;
+
+fn render_with_threads(threads: usize) -> Film {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    pool.install(|| {
+        common::viewport().render(WIDTH, HEIGHT, 4, RAY_DEPTH, rusttracer::debugging_scene())
+    })
+}
+
+#[test]
+fn thread_count_does_not_change_the_image() {
+    common::assert_films_equal(&render_with_threads(1), &render_with_threads(4));
+}
+
+#[test]
+fn equal_seeds_render_equal_images() {
+    common::assert_films_equal(&render_with_threads(4), &render_with_threads(4));
+}